@@ -0,0 +1,106 @@
+/* Generated by cbindgen from capi/src/lib.rs, do not edit. */
+
+#ifndef VADER_SENTIMENT_H
+#define VADER_SENTIMENT_H
+
+#include <stddef.h>
+
+/**
+ * Result of every fallible call
+ */
+typedef enum VaderStatus {
+  VADER_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  VADER_STATUS_NULL_POINTER = 1,
+  /**
+   * The text or lexicon was not valid UTF-8
+   */
+  VADER_STATUS_INVALID_UTF8 = 2,
+  /**
+   * The lexicon buffer could not be parsed
+   */
+  VADER_STATUS_INVALID_LEXICON = 3,
+  /**
+   * The analyzer panicked, which is a bug in vader_sentiment
+   */
+  VADER_STATUS_PANIC = 4,
+} VaderStatus;
+
+/**
+ * An analyzer created by `vader_analyzer_new` or `vader_analyzer_from_lexicon`. It is immutable,
+ * so one analyzer can be used from several threads at once.
+ */
+typedef struct VaderAnalyzer VaderAnalyzer;
+
+/**
+ * The scores of a text, as in the "neg", "neu", "pos" and "compound" keys of the original
+ */
+typedef struct VaderScores {
+  double neg;
+  double neu;
+  double pos;
+  double compound;
+} VaderScores;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an analyzer with the built-in lexicon and stores it in `*out`.
+ * Free it with `vader_analyzer_free`.
+ *
+ * # Safety
+ * `out` must be NULL or valid for writes.
+ */
+enum VaderStatus vader_analyzer_new(struct VaderAnalyzer **out);
+
+/**
+ * Creates an analyzer with a custom lexicon, given as the `len` bytes of a buffer in the format
+ * of vader_lexicon.txt (or just `word<TAB>valence` per line), and stores it in `*out`. The buffer
+ * is copied and doesn't need to be NUL terminated. If the lexicon can't be parsed and
+ * `error_line` isn't NULL, the 1-based line of the error is stored in it.
+ *
+ * # Safety
+ * `lexicon` must be valid for reads of `len` bytes, `out` must be NULL or valid for writes, and
+ * `error_line` must be NULL or valid for writes.
+ */
+enum VaderStatus vader_analyzer_from_lexicon(const char *lexicon,
+                                             size_t len,
+                                             struct VaderAnalyzer **out,
+                                             size_t *error_line);
+
+/**
+ * Frees an analyzer. Passing NULL does nothing.
+ *
+ * # Safety
+ * `analyzer` must be NULL or an analyzer that hasn't been freed yet.
+ */
+void vader_analyzer_free(struct VaderAnalyzer *analyzer);
+
+/**
+ * Scores the `len` bytes of UTF-8 text at `text` into `*out`. The text doesn't need to be NUL
+ * terminated, and may be NULL if `len` is 0.
+ *
+ * # Safety
+ * `analyzer` must be NULL or a live analyzer, `text` must be valid for reads of `len` bytes, and
+ * `out` must be NULL or valid for writes.
+ */
+enum VaderStatus vader_polarity_scores(const struct VaderAnalyzer *analyzer,
+                                       const char *text,
+                                       size_t len,
+                                       struct VaderScores *out);
+
+/**
+ * Returns a static, NUL terminated description of a status, which must not be freed. Values
+ * that aren't a `VaderStatus` get "unknown status".
+ */
+const char *vader_status_message(int status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* VADER_SENTIMENT_H */
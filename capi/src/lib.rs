@@ -0,0 +1,178 @@
+/*!
+ * C API for vader_sentiment, for C, C++, Go (cgo) and anything else that can call a C library.
+ * The declarations are in `include/vader_sentiment.h`:
+ *
+ * ```c
+ * VaderAnalyzer *analyzer = NULL;
+ * VaderScores scores;
+ * const char *text = "VADER is smart, handsome, and funny.";
+ *
+ * if (vader_analyzer_new(&analyzer) == VADER_STATUS_OK &&
+ *     vader_polarity_scores(analyzer, text, strlen(text), &scores) == VADER_STATUS_OK) {
+ *     printf("%f\n", scores.compound);
+ * }
+ * vader_analyzer_free(analyzer);
+ * ```
+ *
+ * No function panics across the boundary: failures, including panics inside the analyzer, are
+ * reported as a `VaderStatus`, and outputs are only written on `VADER_STATUS_OK`.
+ **/
+
+use std::ffi::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::str;
+use std::sync::Arc;
+
+use vader_sentiment::{Lexicon, OwnedSentimentIntensityAnalyzer};
+
+/// An analyzer created by `vader_analyzer_new` or `vader_analyzer_from_lexicon`. It is immutable,
+/// so one analyzer can be used from several threads at once.
+pub struct VaderAnalyzer {
+    analyzer: OwnedSentimentIntensityAnalyzer,
+}
+
+/// The scores of a text, as in the "neg", "neu", "pos" and "compound" keys of the original
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VaderScores {
+    pub neg: f64,
+    pub neu: f64,
+    pub pos: f64,
+    pub compound: f64,
+}
+
+/// Result of every fallible call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaderStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// The text or lexicon was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// The lexicon buffer could not be parsed
+    InvalidLexicon = 3,
+    /// The analyzer panicked, which is a bug in vader_sentiment
+    Panic = 4,
+}
+
+/// Creates an analyzer with the built-in lexicon and stores it in `*out`.
+/// Free it with `vader_analyzer_free`.
+///
+/// # Safety
+/// `out` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn vader_analyzer_new(out: *mut *mut VaderAnalyzer) -> VaderStatus {
+    if out.is_null() {
+        return VaderStatus::NullPointer;
+    }
+    guard(|| {
+        let analyzer = OwnedSentimentIntensityAnalyzer::new();
+        *out = Box::into_raw(Box::new(VaderAnalyzer { analyzer }));
+        VaderStatus::Ok
+    })
+}
+
+/// Creates an analyzer with a custom lexicon, given as the `len` bytes of a buffer in the format
+/// of vader_lexicon.txt (or just `word<TAB>valence` per line), and stores it in `*out`. The buffer
+/// is copied and doesn't need to be NUL terminated. If the lexicon can't be parsed and
+/// `error_line` isn't NULL, the 1-based line of the error is stored in it.
+///
+/// # Safety
+/// `lexicon` must be valid for reads of `len` bytes, `out` must be NULL or valid for writes, and
+/// `error_line` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn vader_analyzer_from_lexicon(lexicon: *const c_char, len: usize, out: *mut *mut VaderAnalyzer,
+                                                     error_line: *mut usize) -> VaderStatus {
+    if out.is_null() {
+        return VaderStatus::NullPointer;
+    }
+    let raw = match text_arg(lexicon, len) {
+        Ok(raw) => raw,
+        Err(status) => return status,
+    };
+    guard(|| {
+        let lexicon = match raw.parse::<Lexicon>() {
+            Ok(lexicon) => lexicon,
+            Err(e) => {
+                if !error_line.is_null() {
+                    *error_line = e.line;
+                }
+                return VaderStatus::InvalidLexicon;
+            },
+        };
+        let analyzer = OwnedSentimentIntensityAnalyzer::from_shared_lexicon(Arc::new(lexicon));
+        *out = Box::into_raw(Box::new(VaderAnalyzer { analyzer }));
+        VaderStatus::Ok
+    })
+}
+
+/// Frees an analyzer. Passing NULL does nothing.
+///
+/// # Safety
+/// `analyzer` must be NULL or an analyzer that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn vader_analyzer_free(analyzer: *mut VaderAnalyzer) {
+    if !analyzer.is_null() {
+        // Dropping only frees memory, but a panic must not unwind into C either way
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(analyzer))));
+    }
+}
+
+/// Scores the `len` bytes of UTF-8 text at `text` into `*out`. The text doesn't need to be NUL
+/// terminated, and may be NULL if `len` is 0.
+///
+/// # Safety
+/// `analyzer` must be NULL or a live analyzer, `text` must be valid for reads of `len` bytes, and
+/// `out` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn vader_polarity_scores(analyzer: *const VaderAnalyzer, text: *const c_char, len: usize,
+                                               out: *mut VaderScores) -> VaderStatus {
+    if analyzer.is_null() || out.is_null() {
+        return VaderStatus::NullPointer;
+    }
+    let text = match text_arg(text, len) {
+        Ok(text) => text,
+        Err(status) => return status,
+    };
+    guard(|| {
+        let scores = (*analyzer).analyzer.polarity_scores(text);
+        *out = VaderScores { neg: scores.neg, neu: scores.neu, pos: scores.pos, compound: scores.compound };
+        VaderStatus::Ok
+    })
+}
+
+/// Returns a static, NUL terminated description of a status, which must not be freed. Values
+/// that aren't a `VaderStatus` get "unknown status".
+#[no_mangle]
+pub extern "C" fn vader_status_message(status: c_int) -> *const c_char {
+    // Taken as an int, since a C caller can pass any value and a Rust enum must hold a valid one
+    let statuses = [VaderStatus::Ok, VaderStatus::NullPointer, VaderStatus::InvalidUtf8,
+                    VaderStatus::InvalidLexicon, VaderStatus::Panic];
+    let message: &'static [u8] = match statuses.iter().find(|&&known| known as c_int == status) {
+        Some(VaderStatus::Ok) => b"ok\0",
+        Some(VaderStatus::NullPointer) => b"a required pointer argument was NULL\0",
+        Some(VaderStatus::InvalidUtf8) => b"the input is not valid UTF-8\0",
+        Some(VaderStatus::InvalidLexicon) => b"the lexicon could not be parsed\0",
+        Some(VaderStatus::Panic) => b"internal error in vader_sentiment\0",
+        None => b"unknown status\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+// Borrows a (pointer, length) argument as a str
+unsafe fn text_arg<'a>(text: *const c_char, len: usize) -> Result<&'a str, VaderStatus> {
+    if len == 0 {
+        return Ok("");
+    }
+    if text.is_null() {
+        return Err(VaderStatus::NullPointer);
+    }
+    str::from_utf8(slice::from_raw_parts(text as *const u8, len)).map_err(|_| VaderStatus::InvalidUtf8)
+}
+
+// Runs the body of an exported function, turning a panic into a status
+fn guard<F: FnOnce() -> VaderStatus>(body: F) -> VaderStatus {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(VaderStatus::Panic)
+}
@@ -0,0 +1,107 @@
+/* Exercises the C API the way a C caller would. Built and run by tests/c_api.rs. */
+
+#include <math.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "vader_sentiment.h"
+
+static int failures = 0;
+
+#define CHECK(condition) do { \
+        if (!(condition)) { \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            failures++; \
+        } \
+    } while (0)
+
+static VaderStatus score(const VaderAnalyzer *analyzer, const char *text, VaderScores *out) {
+    return vader_polarity_scores(analyzer, text, strlen(text), out);
+}
+
+static void test_default_analyzer(void) {
+    VaderAnalyzer *analyzer = NULL;
+    VaderScores scores;
+
+    CHECK(vader_analyzer_new(&analyzer) == VADER_STATUS_OK);
+    CHECK(analyzer != NULL);
+
+    CHECK(score(analyzer, "VADER is smart, handsome, and funny.", &scores) == VADER_STATUS_OK);
+    CHECK(fabs(scores.compound - 0.8316320352807864) < 1e-12);
+    CHECK(scores.neg == 0.0);
+    CHECK(fabs(scores.neu + scores.pos - 1.0) < 1e-12);
+
+    CHECK(score(analyzer, "The book was kind of good.", &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound > 0.0);
+    CHECK(score(analyzer, "Today SUX!", &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound < 0.0);
+
+    /* Only the first len bytes are scored, so the text doesn't need a NUL terminator */
+    CHECK(vader_polarity_scores(analyzer, "good terrible", 4, &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound > 0.0);
+
+    CHECK(vader_polarity_scores(analyzer, NULL, 0, &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound == 0.0);
+
+    vader_analyzer_free(analyzer);
+}
+
+static void test_errors(void) {
+    VaderAnalyzer *analyzer = NULL;
+    VaderScores scores = { 1.0, 2.0, 3.0, 4.0 };
+    const char invalid_utf8[] = { 'g', 'o', 'o', 'd', ' ', (char) 0xff, (char) 0xfe };
+
+    CHECK(vader_analyzer_new(NULL) == VADER_STATUS_NULL_POINTER);
+    CHECK(vader_analyzer_new(&analyzer) == VADER_STATUS_OK);
+
+    CHECK(vader_polarity_scores(NULL, "good", 4, &scores) == VADER_STATUS_NULL_POINTER);
+    CHECK(vader_polarity_scores(analyzer, "good", 4, NULL) == VADER_STATUS_NULL_POINTER);
+    CHECK(vader_polarity_scores(analyzer, NULL, 4, &scores) == VADER_STATUS_NULL_POINTER);
+    CHECK(vader_polarity_scores(analyzer, invalid_utf8, sizeof invalid_utf8, &scores) == VADER_STATUS_INVALID_UTF8);
+    /* Failed calls leave the output alone */
+    CHECK(scores.neg == 1.0 && scores.compound == 4.0);
+
+    CHECK(strcmp(vader_status_message(VADER_STATUS_OK), "ok") == 0);
+    CHECK(strstr(vader_status_message(VADER_STATUS_INVALID_UTF8), "UTF-8") != NULL);
+    CHECK(strcmp(vader_status_message(42), "unknown status") == 0);
+
+    vader_analyzer_free(analyzer);
+    vader_analyzer_free(NULL);
+}
+
+static void test_custom_lexicon(void) {
+    const char lexicon[] = "bullish\t2.5\nbearish\t-2.5\n";
+    const char broken[] = "bullish\t2.5\nbearish\tvery\n";
+    VaderAnalyzer *analyzer = NULL;
+    VaderScores scores;
+    size_t error_line = 0;
+
+    CHECK(vader_analyzer_from_lexicon(lexicon, strlen(lexicon), &analyzer, &error_line) == VADER_STATUS_OK);
+    CHECK(score(analyzer, "Analysts are bullish", &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound > 0.0);
+    CHECK(score(analyzer, "Analysts are bearish", &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound < 0.0);
+    /* Words of the built-in lexicon are gone */
+    CHECK(score(analyzer, "good", &scores) == VADER_STATUS_OK);
+    CHECK(scores.compound == 0.0);
+    vader_analyzer_free(analyzer);
+
+    analyzer = NULL;
+    CHECK(vader_analyzer_from_lexicon(broken, strlen(broken), &analyzer, &error_line) == VADER_STATUS_INVALID_LEXICON);
+    CHECK(error_line == 2);
+    CHECK(analyzer == NULL);
+    CHECK(vader_analyzer_from_lexicon(broken, strlen(broken), &analyzer, NULL) == VADER_STATUS_INVALID_LEXICON);
+    CHECK(vader_analyzer_from_lexicon(lexicon, strlen(lexicon), NULL, NULL) == VADER_STATUS_NULL_POINTER);
+}
+
+int main(void) {
+    test_default_analyzer();
+    test_errors();
+    test_custom_lexicon();
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("all checks passed\n");
+    return 0;
+}
@@ -0,0 +1,43 @@
+//! Compiles tests/c/test_vader.c against the committed header and the cdylib that cargo built
+//! for this test, then runs it. Set CC to use another compiler than `cc`.
+
+#![cfg(unix)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// The cdylib sits next to the test executable in target/<profile>/deps
+fn library_dir() -> PathBuf {
+    let exe = env::current_exe().expect("test executable path");
+    exe.parent().expect("deps directory").to_path_buf()
+}
+
+#[test]
+fn c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = library_dir();
+    assert!(lib_dir.join(format!("{}vader_sentiment_capi{}", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX)).exists(),
+            "cdylib not found in {}", lib_dir.display());
+    let exe = Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_vader");
+
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I").arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/test_vader.c"))
+        .arg("-o").arg(&exe)
+        .arg("-L").arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lvader_sentiment_capi")
+        .arg("-lm")
+        .status()
+        .unwrap_or_else(|e| panic!("couldn't run {}: {}", compiler, e));
+    assert!(status.success(), "compiling test_vader.c failed");
+
+    let output = Command::new(&exe).output().expect("couldn't run test_vader");
+    assert!(output.status.success(), "test_vader failed:\n{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "all checks passed\n");
+}
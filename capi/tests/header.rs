@@ -0,0 +1,24 @@
+//! Checks that the committed header is what cbindgen generates from the current source
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn header_is_up_to_date() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(manifest_dir.join("cbindgen.toml")).expect("cbindgen.toml");
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(manifest_dir.join("src/lib.rs"))
+        .generate()
+        .expect("cbindgen failed")
+        .write(&mut generated);
+    let committed = fs::read(manifest_dir.join("include/vader_sentiment.h")).expect("include/vader_sentiment.h");
+    if generated != committed {
+        let fresh = Path::new(env!("CARGO_TARGET_TMPDIR")).join("vader_sentiment.h");
+        fs::write(&fresh, &generated).unwrap();
+        panic!("include/vader_sentiment.h is out of date, see {} or regenerate it with \
+                `cbindgen --config cbindgen.toml --output include/vader_sentiment.h`", fresh.display());
+    }
+}
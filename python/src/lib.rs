@@ -0,0 +1,98 @@
+/*!
+ * Python bindings for vader_sentiment. The `SentimentIntensityAnalyzer` class mirrors the one
+ * from the vaderSentiment package, so it can replace it without changing calling code:
+ *
+ * ```python
+ * from vader_sentiment import SentimentIntensityAnalyzer
+ *
+ * analyzer = SentimentIntensityAnalyzer()
+ * analyzer.polarity_scores("VADER is smart, handsome, and funny.")
+ * # {'neg': 0.0, 'neu': 0.254, 'pos': 0.746, 'compound': 0.8316}
+ * ```
+ **/
+
+use std::sync::Arc;
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use vader_sentiment::{Compat, Lexicon, LexiconErrorKind, OwnedSentimentIntensityAnalyzer, SentimentIntensityAnalyzer,
+                      SentimentScores, VaderConfig};
+
+// Default file names of the Python package, which mean the lexicons built into this crate
+static DEFAULT_LEXICON_FILE: &str = "vader_lexicon.txt";
+static DEFAULT_EMOJI_LEXICON_FILE: &str = "emoji_utf8_lexicon.txt";
+
+#[pyclass(name = "SentimentIntensityAnalyzer", module = "vader_sentiment", frozen)]
+struct PySentimentIntensityAnalyzer {
+    analyzer: OwnedSentimentIntensityAnalyzer,
+}
+
+#[pymethods]
+impl PySentimentIntensityAnalyzer {
+    /// Creates an analyzer with the built-in lexicon, or with the lexicon file at `lexicon_file`.
+    #[new]
+    #[pyo3(signature = (lexicon_file = DEFAULT_LEXICON_FILE, emoji_lexicon = DEFAULT_EMOJI_LEXICON_FILE))]
+    fn new(lexicon_file: &str, emoji_lexicon: &str) -> PyResult<Self> {
+        if emoji_lexicon != DEFAULT_EMOJI_LEXICON_FILE {
+            return Err(PyValueError::new_err("custom emoji lexicons are not supported"));
+        }
+        // Scores have to match the package's, quirks included
+        let config = VaderConfig { compat: Compat::Python, ..VaderConfig::default() };
+        let mut builder = SentimentIntensityAnalyzer::builder().config(config);
+        if lexicon_file != DEFAULT_LEXICON_FILE {
+            let lexicon = Lexicon::from_path_python(lexicon_file).map_err(|e| match e.kind {
+                LexiconErrorKind::Io(_) => PyOSError::new_err(format!("{}: {}", lexicon_file, e)),
+                _ => PyValueError::new_err(format!("{}: {}", lexicon_file, e)),
+            })?;
+            builder = builder.shared_lexicon(Arc::new(lexicon));
+        }
+        let analyzer: OwnedSentimentIntensityAnalyzer = builder.build();
+        Ok(PySentimentIntensityAnalyzer { analyzer })
+    }
+
+    /// Returns a dict with the "neg", "neu", "pos" and "compound" scores of the text, rounded
+    /// like the vaderSentiment package rounds them.
+    fn polarity_scores<'py>(&self, py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyDict>> {
+        scores_dict(py, &self.analyzer.polarity_scores(text))
+    }
+
+    /// Scores a list of texts without holding the GIL, so other Python threads keep running.
+    /// With `parallel=True` the texts are scored on all cores. Either way the dicts are in the
+    /// order of the texts.
+    #[pyo3(signature = (texts, parallel = false))]
+    fn polarity_scores_batch<'py>(&self, py: Python<'py>, texts: Vec<String>, parallel: bool)
+                                  -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let scores = py.detach(|| {
+            if parallel {
+                self.analyzer.polarity_scores_par_batch(&texts)
+            } else {
+                self.analyzer.polarity_scores_batch(&texts)
+            }
+        });
+        scores.iter().map(|scores| scores_dict(py, scores)).collect()
+    }
+}
+
+fn scores_dict<'py>(py: Python<'py>, scores: &SentimentScores) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("neg", round(scores.neg, 3))?;
+    dict.set_item("neu", round(scores.neu, 3))?;
+    dict.set_item("pos", round(scores.pos, 3))?;
+    dict.set_item("compound", round(scores.compound, 4))?;
+    Ok(dict)
+}
+
+// Rounds like Python's round(): to the nearest decimal, ties to even, using the exact value of
+// the float. Formatting does exactly that, which plain multiply-and-round doesn't.
+fn round(value: f64, digits: usize) -> f64 {
+    format!("{:.*}", digits, value).parse().unwrap_or(value)
+}
+
+#[pymodule(name = "vader_sentiment")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySentimentIntensityAnalyzer>()?;
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+import threading
+
+import pytest
+
+from vader_sentiment import SentimentIntensityAnalyzer
+
+# Outputs of the vaderSentiment package for the examples in its README
+EXPECTED = {
+    "VADER is smart, handsome, and funny.": {"neg": 0.0, "neu": 0.254, "pos": 0.746, "compound": 0.8316},
+    "VADER is smart, handsome, and funny!": {"neg": 0.0, "neu": 0.248, "pos": 0.752, "compound": 0.8439},
+    "VADER is very smart, handsome, and funny.": {"neg": 0.0, "neu": 0.299, "pos": 0.701, "compound": 0.8545},
+    "VADER is VERY SMART, handsome, and FUNNY.": {"neg": 0.0, "neu": 0.246, "pos": 0.754, "compound": 0.9227},
+    "VADER is not smart, handsome, nor funny.": {"neg": 0.646, "neu": 0.354, "pos": 0.0, "compound": -0.7424},
+    "The book was good.": {"neg": 0.0, "neu": 0.508, "pos": 0.492, "compound": 0.4404},
+    "Today SUX!": {"neg": 0.779, "neu": 0.221, "pos": 0.0, "compound": -0.5461},
+}
+
+
+@pytest.fixture(scope="module")
+def analyzer():
+    return SentimentIntensityAnalyzer()
+
+
+@pytest.mark.parametrize("text", sorted(EXPECTED))
+def test_polarity_scores(analyzer, text):
+    scores = analyzer.polarity_scores(text)
+    assert scores == EXPECTED[text]
+    assert list(scores) == ["neg", "neu", "pos", "compound"]
+
+
+def test_batch_matches_single_texts(analyzer):
+    texts = sorted(EXPECTED) * 50 + ["", "😁 lol"]
+    expected = [analyzer.polarity_scores(text) for text in texts]
+    assert analyzer.polarity_scores_batch(texts) == expected
+    assert analyzer.polarity_scores_batch(texts, parallel=True) == expected
+    assert analyzer.polarity_scores_batch([]) == []
+
+
+def test_batch_releases_the_gil(analyzer):
+    texts = ["VADER is VERY SMART, uber handsome, and FRIGGIN FUNNY!!!"] * 20000
+    ticks = []
+    done = threading.Event()
+
+    def tick():
+        while not done.is_set():
+            ticks.append(1)
+            done.wait(0.001)
+
+    thread = threading.Thread(target=tick)
+    thread.start()
+    try:
+        before = len(ticks)
+        analyzer.polarity_scores_batch(texts)
+        during = len(ticks) - before
+    finally:
+        done.set()
+        thread.join()
+    # The other thread only gets to tick while the batch runs if the GIL was released
+    assert during > 1
+
+
+def test_custom_lexicon(tmp_path):
+    lexicon = tmp_path / "lexicon.txt"
+    lexicon.write_text("bullish\t2.5\t0.5\t[2, 3, 3, 2, 2, 3, 3, 2, 3, 2]\n")
+    analyzer = SentimentIntensityAnalyzer(lexicon_file=str(lexicon))
+    assert analyzer.polarity_scores("very bullish")["compound"] > 0
+    assert analyzer.polarity_scores("good")["compound"] == 0
+
+    with pytest.raises(OSError):
+        SentimentIntensityAnalyzer(lexicon_file=str(tmp_path / "missing.txt"))
+    lexicon.write_text("bullish\tvery\n")
+    with pytest.raises(ValueError):
+        SentimentIntensityAnalyzer(lexicon_file=str(lexicon))
+    with pytest.raises(ValueError):
+        SentimentIntensityAnalyzer(emoji_lexicon="my_emoji.txt")
+
+
+def test_matches_vader_sentiment(analyzer):
+    vader = pytest.importorskip("vaderSentiment.vaderSentiment")
+    reference = vader.SentimentIntensityAnalyzer()
+    for text in EXPECTED:
+        assert analyzer.polarity_scores(text) == reference.polarity_scores(text)
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use vader_sentiment::LabelThresholds;
+
+pub static USAGE: &str = "\
+Usage: vader_sentiment [OPTIONS] [FILE]...
+
+Scores the sentiment of text read from the given files, or from stdin if there are none.
+A FILE of - also reads stdin.
+
+Options:
+  -i, --input FORMAT     How to read documents from the input [default: lines]
+                           lines  one document per line
+                           text   one document per file
+                           csv    the text column of a CSV file with a header row
+                           tsv    the text column of a TSV file with a header row
+                           jsonl  a string field of one JSON object per line
+  -c, --column NAME      Column holding the text in csv/tsv input, either a header name or
+                         a 1-based index [default: text]
+  -f, --field NAME       Field holding the text in jsonl input [default: text]
+  -o, --output FORMAT    tsv, csv, jsonl or table [default: tsv]
+  -e, --explain          Include how every token was scored
+  -l, --lexicon PATH     Use a custom lexicon instead of the built-in one
+      --positive SCORE   Smallest compound score labeled positive [default: 0.05]
+      --negative SCORE   Largest compound score labeled negative [default: -0.05]
+      --demo             Score the examples from the original implementation
+  -h, --help             Print this message
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Lines,
+    Text,
+    Csv,
+    Tsv,
+    Jsonl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Tsv,
+    Csv,
+    Jsonl,
+    Table,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub files: Vec<PathBuf>,
+    pub input: InputFormat,
+    pub column: String,
+    pub field: String,
+    pub output: OutputFormat,
+    pub explain: bool,
+    pub lexicon: Option<PathBuf>,
+    pub thresholds: LabelThresholds,
+    pub demo: bool,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            files: Vec::new(),
+            input: InputFormat::Lines,
+            column: "text".to_string(),
+            field: "text".to_string(),
+            output: OutputFormat::Tsv,
+            explain: false,
+            lexicon: None,
+            thresholds: LabelThresholds::default(),
+            demo: false,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    //Parses the command line arguments, without the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        let mut only_files = false;
+        while let Some(arg) = args.next() {
+            if only_files || arg == "-" || !arg.starts_with('-') {
+                options.files.push(PathBuf::from(arg));
+                continue;
+            }
+            // Accept both "--output jsonl" and "--output=jsonl"
+            let (flag, inline_value) = match arg.find('=') {
+                Some(i) if arg.starts_with("--") => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", flag));
+            match flag.as_str() {
+                "--" => only_files = true,
+                "-i" | "--input" => options.input = parse_input(&value()?)?,
+                "-c" | "--column" => options.column = value()?,
+                "-f" | "--field" => options.field = value()?,
+                "-o" | "--output" => options.output = parse_output(&value()?)?,
+                "-e" | "--explain" => options.explain = true,
+                "-l" | "--lexicon" => options.lexicon = Some(PathBuf::from(value()?)),
+                "--positive" => options.thresholds.positive = parse_score(&flag, &value()?)?,
+                "--negative" => options.thresholds.negative = parse_score(&flag, &value()?)?,
+                "--demo" => options.demo = true,
+                "-h" | "--help" => options.help = true,
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        if options.thresholds.negative > options.thresholds.positive {
+            return Err("--negative can't be larger than --positive".to_string());
+        }
+        Ok(options)
+    }
+}
+
+fn parse_input(format: &str) -> Result<InputFormat, String> {
+    match format {
+        "lines" => Ok(InputFormat::Lines),
+        "text" => Ok(InputFormat::Text),
+        "csv" => Ok(InputFormat::Csv),
+        "tsv" => Ok(InputFormat::Tsv),
+        "jsonl" => Ok(InputFormat::Jsonl),
+        _ => Err(format!("unknown input format {}", format)),
+    }
+}
+
+fn parse_output(format: &str) -> Result<OutputFormat, String> {
+    match format {
+        "tsv" => Ok(OutputFormat::Tsv),
+        "csv" => Ok(OutputFormat::Csv),
+        "jsonl" => Ok(OutputFormat::Jsonl),
+        "table" => Ok(OutputFormat::Table),
+        _ => Err(format!("unknown output format {}", format)),
+    }
+}
+
+fn parse_score(flag: &str, score: &str) -> Result<f64, String> {
+    match score.parse::<f64>() {
+        Ok(score) if (-1.0..=1.0).contains(&score) => Ok(score),
+        _ => Err(format!("{} must be a number between -1 and 1, got {}", flag, score)),
+    }
+}
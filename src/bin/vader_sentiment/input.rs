@@ -0,0 +1,131 @@
+use std::error::Error;
+use std::io::{self, BufRead};
+
+use args::{InputFormat, Options};
+use json;
+
+/**
+ * Reads the documents to score from one input, calling `score` with each of them in order.
+ * Errors name the line of the input they happened on.
+ **/
+pub fn for_each_document<R, F>(mut reader: R, options: &Options, mut score: F) -> Result<(), Box<dyn Error>>
+    where R: BufRead, F: FnMut(&str) -> io::Result<()> {
+    match options.input {
+        InputFormat::Text => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            score(&text)?;
+        },
+        InputFormat::Lines => {
+            for line in reader.lines() {
+                score(line?.trim_end_matches('\r'))?;
+            }
+        },
+        InputFormat::Jsonl => {
+            for (i, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let text = json::string_field(&line, &options.field)
+                    .map_err(|e| format!("line {}: {}", i + 1, e))?
+                    .ok_or_else(|| format!("line {}: no \"{}\" field", i + 1, options.field))?;
+                score(&text)?;
+            }
+        },
+        InputFormat::Csv | InputFormat::Tsv => {
+            let mut records = RecordReader::new(reader, options.input == InputFormat::Csv);
+            let header = match records.next_record()? {
+                Some(header) => header,
+                None => return Ok(()),
+            };
+            let column = find_column(&header, &options.column)?;
+            while let Some(record) = records.next_record()? {
+                let text = record.get(column).ok_or_else(|| {
+                    format!("line {}: expected at least {} columns, found {}", records.line_no, column + 1, record.len())
+                })?;
+                score(text)?;
+            }
+        },
+    }
+    Ok(())
+}
+
+//Finds a column by header name, falling back to a 1-based index
+fn find_column(header: &[String], column: &str) -> Result<usize, String> {
+    if let Some(i) = header.iter().position(|name| name == column) {
+        return Ok(i);
+    }
+    match column.parse::<usize>() {
+        Ok(i) if i >= 1 && i <= header.len() => Ok(i - 1),
+        _ => Err(format!("no column named {} in the header", column)),
+    }
+}
+
+/**
+ * Splits CSV or TSV input into records. CSV fields can be quoted, in which case they may
+ * contain commas, doubled quotes and line breaks. TSV fields are split on every tab.
+ **/
+pub struct RecordReader<R> {
+    reader: R,
+    csv: bool,
+    // Line the last record started on
+    pub line_no: usize,
+    lines_read: usize,
+}
+
+impl<R: BufRead> RecordReader<R> {
+    pub fn new(reader: R, csv: bool) -> RecordReader<R> {
+        RecordReader { reader, csv, line_no: 0, lines_read: 0 }
+    }
+
+    pub fn next_record(&mut self) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.lines_read += 1;
+            if !line.trim_end_matches(&['\r', '\n'][..]).is_empty() {
+                break;
+            }
+        }
+        self.line_no = self.lines_read;
+        if !self.csv {
+            let line = line.trim_end_matches(&['\r', '\n'][..]);
+            return Ok(Some(line.split('\t').map(str::to_string).collect()));
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        loop {
+            let mut chars = line.chars().peekable();
+            while let Some(chr) = chars.next() {
+                match chr {
+                    '"' if in_quotes && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    '"' if in_quotes => in_quotes = false,
+                    '"' if field.is_empty() => in_quotes = true,
+                    ',' if !in_quotes => fields.push(::std::mem::take(&mut field)),
+                    '\r' | '\n' if !in_quotes => {},
+                    chr => field.push(chr),
+                }
+            }
+            if !in_quotes {
+                break;
+            }
+            // The quoted field continues on the next line
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(format!("line {}: unterminated quoted field", self.line_no).into());
+            }
+            self.lines_read += 1;
+        }
+        fields.push(field);
+        Ok(Some(fields))
+    }
+}
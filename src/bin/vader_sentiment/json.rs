@@ -0,0 +1,218 @@
+/*!
+ * Just enough JSON to read a string field from an object and to write the output rows
+ **/
+
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+// Arrays and objects nested deeper than this are rejected rather than overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+//Finds a top level string field in a JSON object, or None if the object doesn't have it
+pub fn string_field(json: &str, field: &str) -> Result<Option<String>, String> {
+    let mut parser = Parser { json, chars: json.char_indices().peekable(), depth: 0 };
+    let mut found = None;
+    parser.expect('{')?;
+    if !parser.eat('}') {
+        loop {
+            let key = parser.string()?;
+            parser.expect(':')?;
+            parser.skip_whitespace();
+            if key == field {
+                let start = parser.position();
+                if parser.json[start..].starts_with('"') {
+                    found = Some(parser.string()?);
+                } else if parser.json[start..].starts_with("null") {
+                    parser.value()?;
+                } else {
+                    return Err(format!("field \"{}\" is not a string", field));
+                }
+            } else {
+                parser.value()?;
+            }
+            if parser.eat('}') {
+                break;
+            }
+            parser.expect(',')?;
+        }
+    }
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(found),
+        Some((i, _)) => Err(format!("unexpected data after the object at byte {}", i)),
+    }
+}
+
+struct Parser<'a> {
+    json: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn position(&mut self) -> usize {
+        let len = self.json.len();
+        self.chars.peek().map_or(len, |&(i, _)| i)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, ' ')) | Some(&(_, '\t')) | Some(&(_, '\n')) | Some(&(_, '\r')) = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some(&(_, chr)) if chr == expected => {
+                self.chars.next();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at byte {}", expected, self.position()))
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut result = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(result),
+                Some((i, '\\')) => {
+                    let escaped = match self.chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => self.unicode_escape(i)?,
+                        _ => return Err(format!("invalid escape at byte {}", i)),
+                    };
+                    result.push(escaped);
+                },
+                Some((_, chr)) => result.push(chr),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    // Reads the XXXX of a \uXXXX escape, along with the low half of a surrogate pair
+    fn unicode_escape(&mut self, start: usize) -> Result<char, String> {
+        let high = self.hex4(start)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !(self.chars.next().map(|c| c.1) == Some('\\') && self.chars.next().map(|c| c.1) == Some('u')) {
+                return Err(format!("unpaired surrogate at byte {}", start));
+            }
+            let low = self.hex4(start)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(format!("unpaired surrogate at byte {}", start));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        ::std::char::from_u32(code).ok_or_else(|| format!("invalid escape at byte {}", start))
+    }
+
+    fn hex4(&mut self, start: usize) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self.chars.next().and_then(|(_, chr)| chr.to_digit(16));
+            code = code * 16 + digit.ok_or_else(|| format!("invalid escape at byte {}", start))?;
+        }
+        Ok(code)
+    }
+
+    // Skips over any JSON value
+    fn value(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.peek().cloned() {
+            Some((_, '"')) => self.string().map(|_| ()),
+            Some((_, '{')) => self.sequence('{', '}', true),
+            Some((_, '[')) => self.sequence('[', ']', false),
+            Some((start, _)) => {
+                while let Some(&(_, chr)) = self.chars.peek() {
+                    if !(chr.is_ascii_alphanumeric() || chr == '-' || chr == '+' || chr == '.') {
+                        break;
+                    }
+                    self.chars.next();
+                }
+                let literal = &self.json[start..self.position()];
+                let valid = literal == "true" || literal == "false" || literal == "null" || literal.parse::<f64>().is_ok();
+                if valid {
+                    Ok(())
+                } else {
+                    Err(format!("invalid value at byte {}", start))
+                }
+            },
+            None => Err("unexpected end of line".to_string()),
+        }
+    }
+
+    fn sequence(&mut self, open: char, close: char, keyed: bool) -> Result<(), String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("nested too deeply at byte {}", self.position()));
+        }
+        self.depth += 1;
+        let result = self.sequence_items(open, close, keyed);
+        self.depth -= 1;
+        result
+    }
+
+    fn sequence_items(&mut self, open: char, close: char, keyed: bool) -> Result<(), String> {
+        self.expect(open)?;
+        if self.eat(close) {
+            return Ok(());
+        }
+        loop {
+            if keyed {
+                self.string()?;
+                self.expect(':')?;
+            }
+            self.value()?;
+            if self.eat(close) {
+                return Ok(());
+            }
+            self.expect(',')?;
+        }
+    }
+}
+
+//Appends text as a quoted JSON string
+pub fn push_string(out: &mut String, text: &str) {
+    out.push('"');
+    for chr in text.chars() {
+        match chr {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", chr as u32);
+            },
+            chr => out.push(chr),
+        }
+    }
+    out.push('"');
+}
+
+pub fn push_number(out: &mut String, number: f64) {
+    if number.is_finite() {
+        let _ = write!(out, "{}", number);
+    } else {
+        out.push_str("null");
+    }
+}
@@ -0,0 +1,86 @@
+extern crate vader_sentiment;
+
+mod args;
+mod input;
+mod json;
+mod output;
+
+#[cfg(test)]
+mod tests;
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::process;
+use std::sync::Arc;
+
+use vader_sentiment::{Lexicon, SentimentIntensityAnalyzer};
+
+use args::{Options, USAGE};
+use output::RowWriter;
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+    if options.help {
+        print!("{}", USAGE);
+        return;
+    }
+    if options.demo {
+        vader_sentiment::demo::run_demo();
+        return;
+    }
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let analyzer = match options.lexicon {
+        Some(ref path) => {
+            let lexicon = Lexicon::from_path_lenient(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            SentimentIntensityAnalyzer::from_shared_lexicon(Arc::new(lexicon))
+        },
+        None => SentimentIntensityAnalyzer::new(),
+    };
+    let stdout = io::stdout();
+    let mut writer = RowWriter::new(BufWriter::new(stdout.lock()), options);
+    writer.write_header()?;
+
+    let mut score = |text: &str| {
+        let written = if options.explain {
+            let explanation = analyzer.polarity_scores_explained(text);
+            writer.write_row(text, &explanation.scores, Some(&explanation))
+        } else {
+            writer.write_row(text, &analyzer.polarity_scores(text), None)
+        };
+        // Stop quietly when the output is piped into something like `head`
+        match written {
+            Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+            written => written,
+        }
+    };
+    if options.files.is_empty() {
+        let stdin = io::stdin();
+        input::for_each_document(stdin.lock(), options, &mut score)?;
+    }
+    for path in &options.files {
+        if path.as_os_str() == "-" {
+            let stdin = io::stdin();
+            input::for_each_document(stdin.lock(), options, &mut score)?;
+        } else {
+            let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            input::for_each_document(BufReader::new(file), options, &mut score)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
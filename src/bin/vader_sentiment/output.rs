@@ -0,0 +1,214 @@
+use std::io::{self, Write};
+
+use vader_sentiment::{Explanation, LabelThresholds, SentimentLabel, SentimentScores};
+
+use args::{Options, OutputFormat};
+use json;
+
+// Longest text shown in a table row
+const TABLE_TEXT_WIDTH: usize = 60;
+
+/**
+ * Writes one row per scored document in the chosen output format
+ **/
+pub struct RowWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    explain: bool,
+    thresholds: LabelThresholds,
+}
+
+impl<W: Write> RowWriter<W> {
+    pub fn new(out: W, options: &Options) -> RowWriter<W> {
+        RowWriter { out, format: options.output, explain: options.explain, thresholds: options.thresholds }
+    }
+
+    pub fn write_header(&mut self) -> io::Result<()> {
+        let mut columns = vec!["neg", "neu", "pos", "compound", "label", "text"];
+        if self.explain {
+            columns.push("explanation");
+        }
+        match self.format {
+            OutputFormat::Tsv => writeln!(self.out, "{}", columns.join("\t")),
+            OutputFormat::Csv => writeln!(self.out, "{}", columns.join(",")),
+            OutputFormat::Table => {
+                writeln!(self.out, "{:>7} {:>7} {:>7} {:>8}  {:<8}  text", "neg", "neu", "pos", "compound", "label")?;
+                writeln!(self.out, "{}", "-".repeat(47 + TABLE_TEXT_WIDTH))
+            },
+            OutputFormat::Jsonl => Ok(()),
+        }
+    }
+
+    //Writes a document's scores. `explanation` is only used when explanations were asked for.
+    pub fn write_row(&mut self, text: &str, scores: &SentimentScores, explanation: Option<&Explanation>) -> io::Result<()> {
+        let label = label_name(scores.label_with(&self.thresholds));
+        let explanation = explanation.filter(|_| self.explain);
+        match self.format {
+            OutputFormat::Tsv | OutputFormat::Csv => {
+                let mut row = vec![scores.neg.to_string(), scores.neu.to_string(), scores.pos.to_string(),
+                                   scores.compound.to_string(), label.to_string(), text.to_string()];
+                if let Some(explanation) = explanation {
+                    row.push(explanation_summary(explanation, "; "));
+                }
+                if self.format == OutputFormat::Tsv {
+                    let row: Vec<String> = row.iter().map(|field| escape_tsv(field)).collect();
+                    writeln!(self.out, "{}", row.join("\t"))
+                } else {
+                    let row: Vec<String> = row.iter().map(|field| escape_csv(field)).collect();
+                    writeln!(self.out, "{}", row.join(","))
+                }
+            },
+            OutputFormat::Jsonl => {
+                let mut row = String::from("{\"neg\":");
+                json::push_number(&mut row, scores.neg);
+                row.push_str(",\"neu\":");
+                json::push_number(&mut row, scores.neu);
+                row.push_str(",\"pos\":");
+                json::push_number(&mut row, scores.pos);
+                row.push_str(",\"compound\":");
+                json::push_number(&mut row, scores.compound);
+                row.push_str(",\"label\":");
+                json::push_string(&mut row, label);
+                row.push_str(",\"text\":");
+                json::push_string(&mut row, text);
+                if let Some(explanation) = explanation {
+                    row.push_str(",\"explanation\":");
+                    push_explanation(&mut row, explanation);
+                }
+                row.push('}');
+                writeln!(self.out, "{}", row)
+            },
+            OutputFormat::Table => {
+                writeln!(self.out, "{:>7.3} {:>7.3} {:>7.3} {:>8.4}  {:<8}  {}", scores.neg, scores.neu, scores.pos,
+                         scores.compound, label, truncate(text, TABLE_TEXT_WIDTH))?;
+                if let Some(explanation) = explanation {
+                    for line in explanation_lines(explanation) {
+                        writeln!(self.out, "{:>36}{}", "", line)?;
+                    }
+                }
+                Ok(())
+            },
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn label_name(label: SentimentLabel) -> &'static str {
+    match label {
+        SentimentLabel::Positive => "positive",
+        SentimentLabel::Neutral => "neutral",
+        SentimentLabel::Negative => "negative",
+    }
+}
+
+//Describes every token that contributed to the score, like "good 1.9 -> 2.109 (Booster)"
+fn explanation_lines(explanation: &Explanation) -> Vec<String> {
+    explanation.tokens.iter()
+        .filter(|token| token.base_valence.is_some() || token.valence != 0.0)
+        .map(|token| {
+            let mut line = format!("{} {} -> {}", token.token, token.base_valence.unwrap_or(0.0), token.valence);
+            if !token.adjustments.is_empty() {
+                let rules: Vec<String> = token.adjustments.iter().map(|a| format!("{:?}", a.rule)).collect();
+                line.push_str(&format!(" ({})", rules.join(", ")));
+            }
+            line
+        })
+        .collect()
+}
+
+fn explanation_summary(explanation: &Explanation, separator: &str) -> String {
+    explanation_lines(explanation).join(separator)
+}
+
+fn push_explanation(out: &mut String, explanation: &Explanation) {
+    out.push_str("{\"tokens\":[");
+    for (i, token) in explanation.tokens.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"token\":");
+        json::push_string(out, &token.token);
+        let span = token.span;
+        out.push_str(&format!(",\"start\":{},\"end\":{},\"char_start\":{},\"char_end\":{}",
+                              span.start, span.end, span.char_start, span.char_end));
+        out.push_str(",\"base_valence\":");
+        match token.base_valence {
+            Some(valence) => json::push_number(out, valence),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"valence\":");
+        json::push_number(out, token.valence);
+        out.push_str(",\"adjustments\":[");
+        for (j, adjustment) in token.adjustments.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"rule\":");
+            json::push_string(out, &format!("{:?}", adjustment.rule));
+            out.push_str(",\"trigger\":");
+            match adjustment.trigger {
+                Some(trigger) => out.push_str(&trigger.to_string()),
+                None => out.push_str("null"),
+            }
+            out.push_str(",\"before\":");
+            json::push_number(out, adjustment.before);
+            out.push_str(",\"after\":");
+            json::push_number(out, adjustment.after);
+            out.push('}');
+        }
+        out.push_str("]}");
+    }
+    out.push_str("],\"idioms\":[");
+    for (i, idiom) in explanation.idioms.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"phrase\":");
+        json::push_string(out, &idiom.phrase);
+        out.push_str(&format!(",\"start\":{},\"end\":{},\"valence\":", idiom.start, idiom.end));
+        json::push_number(out, idiom.valence);
+        out.push('}');
+    }
+    out.push_str("],\"punctuation_amplifier\":");
+    json::push_number(out, explanation.punctuation_amplifier);
+    out.push_str(",\"raw_total\":");
+    json::push_number(out, explanation.normalization.raw_total);
+    out.push('}');
+}
+
+// Tabs and line breaks would split the row, so they are written as \t, \n and \r
+fn escape_tsv(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for chr in field.chars() {
+        match chr {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            chr => escaped.push(chr),
+        }
+    }
+    escaped
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    let text = text.replace(&['\t', '\n', '\r'][..], " ");
+    if text.chars().count() <= width {
+        text
+    } else {
+        let mut truncated: String = text.chars().take(width - 3).collect();
+        truncated.push_str("...");
+        truncated
+    }
+}
@@ -0,0 +1,129 @@
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use args::{InputFormat, Options, OutputFormat};
+use input::{self, RecordReader};
+use json;
+use output::RowWriter;
+
+fn args(args: &[&str]) -> Result<Options, String> {
+    Options::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn args_test() {
+    assert_eq!(args(&[]).unwrap(), Options::default());
+    let options = args(&["-i", "csv", "--column=body", "-o", "jsonl", "--explain", "--positive", "0.5",
+                         "--lexicon", "lex.txt", "a.csv", "-", "--", "--b.csv"]).unwrap();
+    assert_eq!(options.input, InputFormat::Csv);
+    assert_eq!(options.column, "body");
+    assert_eq!(options.output, OutputFormat::Jsonl);
+    assert!(options.explain);
+    assert_eq!(options.thresholds.positive, 0.5);
+    assert_eq!(options.lexicon, Some(PathBuf::from("lex.txt")));
+    assert_eq!(options.files, vec![PathBuf::from("a.csv"), PathBuf::from("-"), PathBuf::from("--b.csv")]);
+
+    assert!(args(&["--output", "xml"]).is_err());
+    assert!(args(&["--field"]).is_err());
+    assert!(args(&["--negative", "0.2", "--positive", "0.1"]).is_err());
+    assert!(args(&["--verbose"]).is_err());
+}
+
+#[test]
+fn json_test() {
+    let line = r#"{"id": 3, "meta": {"tags": ["a", "b"], "ok": true}, "text": "café \"so\" good 😀"}"#;
+    assert_eq!(json::string_field(line, "text").unwrap(), Some("café \"so\" good 😀".to_string()));
+    assert_eq!(json::string_field(line, "body").unwrap(), None);
+    assert_eq!(json::string_field(r#"{"text": null}"#, "text").unwrap(), None);
+    assert!(json::string_field(line, "id").is_err());
+    assert!(json::string_field(r#"{"text": "open"#, "text").is_err());
+    assert!(json::string_field(r#"{"text": "a"} extra"#, "text").is_err());
+    let nested = format!("{{\"id\": {}, \"text\": \"good\"}}", "[".repeat(200_000));
+    assert_eq!(json::string_field(&nested, "text").unwrap_err(), "nested too deeply at byte 135");
+    let nested = format!("{{\"id\": {}{}, \"text\": \"good\"}}", "[".repeat(100), "]".repeat(100));
+    assert_eq!(json::string_field(&nested, "text").unwrap(), Some("good".to_string()));
+
+    let mut out = String::new();
+    json::push_string(&mut out, "tab\there \"quoted\"\n\u{1}");
+    assert_eq!(out, r#""tab\there \"quoted\"\n\u0001""#);
+}
+
+#[test]
+fn record_reader_test() {
+    let csv = "id,text\n1,\"Hello, \"\"world\"\"\"\n\n2,\"two\nlines\"\n3,plain\r\n";
+    let mut reader = RecordReader::new(Cursor::new(csv), true);
+    assert_eq!(reader.next_record().unwrap(), Some(vec!["id".to_string(), "text".to_string()]));
+    assert_eq!(reader.next_record().unwrap(), Some(vec!["1".to_string(), "Hello, \"world\"".to_string()]));
+    assert_eq!(reader.next_record().unwrap(), Some(vec!["2".to_string(), "two\nlines".to_string()]));
+    assert_eq!(reader.line_no, 4);
+    assert_eq!(reader.next_record().unwrap(), Some(vec!["3".to_string(), "plain".to_string()]));
+    assert_eq!(reader.next_record().unwrap(), None);
+
+    let mut reader = RecordReader::new(Cursor::new("a\t\"b\"\tc\n"), false);
+    assert_eq!(reader.next_record().unwrap(), Some(vec!["a".to_string(), "\"b\"".to_string(), "c".to_string()]));
+    assert!(RecordReader::new(Cursor::new("\"open\n"), true).next_record().is_err());
+}
+
+fn documents(input: &str, options: &Options) -> Result<Vec<String>, String> {
+    let mut documents = Vec::new();
+    input::for_each_document(Cursor::new(input), options, |text| {
+        documents.push(text.to_string());
+        Ok(())
+    }).map_err(|e| e.to_string())?;
+    Ok(documents)
+}
+
+#[test]
+fn input_test() {
+    let lines = args(&[]).unwrap();
+    assert_eq!(documents("good\r\nbad\n", &lines).unwrap(), vec!["good", "bad"]);
+    let text = args(&["-i", "text"]).unwrap();
+    assert_eq!(documents("good\nbad\n", &text).unwrap(), vec!["good\nbad\n"]);
+
+    let tsv = args(&["-i", "tsv", "-c", "2"]).unwrap();
+    assert_eq!(documents("id\tbody\n1\tgood\n", &tsv).unwrap(), vec!["good"]);
+    assert_eq!(documents("id\tbody\n1\n", &tsv).unwrap_err(), "line 2: expected at least 2 columns, found 1");
+    let csv = args(&["-i", "csv", "-c", "body"]).unwrap();
+    assert_eq!(documents("id,body\n1,\"good, really\"\n", &csv).unwrap(), vec!["good, really"]);
+    assert!(documents("id,text\n", &csv).is_err());
+
+    let jsonl = args(&["-i", "jsonl", "-f", "body"]).unwrap();
+    assert_eq!(documents("{\"body\": \"good\"}\n\n{\"body\": \"bad\"}\n", &jsonl).unwrap(), vec!["good", "bad"]);
+    assert_eq!(documents("{\"text\": \"good\"}\n", &jsonl).unwrap_err(), "line 1: no \"body\" field");
+}
+
+#[test]
+fn output_test() {
+    let analyzer = ::vader_sentiment::SentimentIntensityAnalyzer::new();
+    let text = "The book was good,\tnot bad";
+    let scores = analyzer.polarity_scores(text);
+    let explanation = analyzer.polarity_scores_explained(text);
+
+    let rows = |options: Options| {
+        let mut out = Vec::new();
+        {
+            let mut writer = RowWriter::new(&mut out, &options);
+            writer.write_header().unwrap();
+            writer.write_row(text, &scores, Some(&explanation)).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    };
+    let tsv = rows(args(&[]).unwrap());
+    assert_eq!(tsv, format!("neg\tneu\tpos\tcompound\tlabel\ttext\n{}\t{}\t{}\t{}\tpositive\tThe book was good,\\tnot bad\n",
+                            scores.neg, scores.neu, scores.pos, scores.compound));
+    let csv = rows(args(&["-o", "csv", "--explain"]).unwrap());
+    assert!(csv.starts_with("neg,neu,pos,compound,label,text,explanation\n"));
+    assert!(csv.contains(",positive,\"The book was good,\tnot bad\",good 1.9 -> 1.9; bad -2.5 -> 1.85 (Negation)\n"));
+    let jsonl = rows(args(&["-o", "jsonl", "--positive", "0.9"]).unwrap());
+    assert!(jsonl.contains("\"label\":\"neutral\",\"text\":\"The book was good,\\tnot bad\"}"));
+    let table = rows(args(&["-o", "table"]).unwrap());
+    assert_eq!(table.lines().count(), 3);
+
+    let mut out = Vec::new();
+    {
+        let options = args(&["-o", "jsonl", "--explain"]).unwrap();
+        let mut writer = RowWriter::new(&mut out, &options);
+        writer.write_row("", &analyzer.polarity_scores(""), Some(&analyzer.polarity_scores_explained(""))).unwrap();
+    }
+    assert!(String::from_utf8(out).unwrap().contains("\"raw_total\":0}"));
+}
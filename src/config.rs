@@ -0,0 +1,107 @@
+//empirically derived constants for scaling/amplifying sentiments
+const B_INCR: f64 =  0.293;
+const B_DECR: f64 = -0.293;
+
+const C_INCR:   f64 =  0.733;
+//there is no empirical value for elongated words ("soooo"), so they count like a booster
+const ELONGATION_INCR: f64 = 0.293;
+const NEGATION_SCALAR: f64 = -0.740;
+
+//sentiment increases for text with question or exclamation marks
+const QMARK_INCR: f64 = 0.180;
+const EMARK_INCR: f64 = 0.292;
+
+//Maximum amount of question or question marks before their contribution to sentiment is
+//disregarded
+const MAX_EMARK: usize = 4;
+const MAX_QMARK: usize = 3;
+const MAX_QMARK_INCR: f64 = 0.96;
+
+const NORMALIZATION_ALPHA: f64 = 15.0;
+
+//weights applied to the words before and after a contrastive "but"
+const BUT_BEFORE_WEIGHT: f64 = 0.5;
+const BUT_AFTER_WEIGHT: f64 = 1.5;
+
+/**
+ * Which rules to follow where this crate's original scoring, the Python implementation
+ * (vaderSentiment 3.3.2) and the corrected rules differ
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compat {
+    // Reproduce vaderSentiment 3.3.2 bug for bug, including its tokenizer, emoji handling,
+    // lexicon lookups, "no" and "at least" rules and extra special case idioms, so scores match
+    // it exactly. Sentiment laden idioms aren't applied, as the original never does.
+    Python,
+    // Score the way this crate always has, with its tokenizer, emoji handling and sentiment
+    // laden idioms
+    #[default]
+    Legacy,
+    // Like `Legacy`, but without its mistakes: "never so"/"never this" only intensifies after a
+    // "never", "at least" and "very least" don't negate, '!' and '?' don't give neutral text a
+    // sentiment, and special idioms, including the ones vaderSentiment 3.3.2 added like "bus
+    // stop", and multi-word boosters are matched on whole tokens, so single boosters aren't
+    // counted twice
+    Corrected,
+}
+
+/**
+ * The constants used for scaling and amplifying sentiments. `VaderConfig::default()` holds
+ * the empirically derived values from the original implementation.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaderConfig {
+    // Added to the valence of a word preceded by a booster ("very", "extremely", ...)
+    pub b_incr: f64,
+    // Added to the valence of a word preceded by a dampener ("kinda", "barely", ...)
+    pub b_decr: f64,
+    // Emphasis for ALL CAPS words in mixed case text
+    pub c_incr: f64,
+    // Whether elongated words like "goooood" are read as their spelling ("good")
+    pub normalize_elongation: bool,
+    // Emphasis for elongated words, when normalize_elongation is on
+    pub elongation_incr: f64,
+    // Whether to tokenize for social media: URLs, emails and @mentions are neutral and
+    // hashtags are split into words ("#NotHappy" -> "Not", "Happy")
+    pub social_media: bool,
+    // Factor applied to negated words
+    pub negation_scalar: f64,
+    // Emphasis per '?' and per '!'. A single '?' adds none in `Compat::Python` mode
+    pub qmark_incr: f64,
+    pub emark_incr: f64,
+    // Number of '!' past which more don't add emphasis
+    pub max_emark: usize,
+    // Number of '?' past which the emphasis is replaced by max_qmark_incr
+    pub max_qmark: usize,
+    pub max_qmark_incr: f64,
+    // Approximate upper limit of a total score, used to normalize the compound score
+    pub normalization_alpha: f64,
+    // Weights for the words before and after a contrastive "but"
+    pub but_before_weight: f64,
+    pub but_after_weight: f64,
+    // Whether to reproduce the mistakes of the Python implementation
+    pub compat: Compat,
+}
+
+impl Default for VaderConfig {
+    fn default() -> VaderConfig {
+        VaderConfig {
+            b_incr: B_INCR,
+            b_decr: B_DECR,
+            c_incr: C_INCR,
+            normalize_elongation: false,
+            elongation_incr: ELONGATION_INCR,
+            social_media: false,
+            negation_scalar: NEGATION_SCALAR,
+            qmark_incr: QMARK_INCR,
+            emark_incr: EMARK_INCR,
+            max_emark: MAX_EMARK,
+            max_qmark: MAX_QMARK,
+            max_qmark_incr: MAX_QMARK_INCR,
+            normalization_alpha: NORMALIZATION_ALPHA,
+            but_before_weight: BUT_BEFORE_WEIGHT,
+            but_after_weight: BUT_AFTER_WEIGHT,
+            compat: Compat::default(),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+/*!
+ * Offline language identification. Latin script text is compared with character trigram
+ * profiles of English, Spanish, Portuguese, German, French, Italian and Dutch, built from the
+ * texts in `resources/lang/samples` by `resources/lang/build_profiles.py`. Text in other
+ * scripts is identified by its script.
+ **/
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use languages::Language;
+use scores::SentimentScores;
+
+// Fewer letters than this are too few to tell languages apart, ie "lol" or "ok"
+const MIN_LETTERS: usize = 4;
+
+// Guesses less confident than this are too close to call to route text away from the
+// analyzer's own language
+const MIN_ROUTING_CONFIDENCE: f64 = 0.15;
+
+// Latin script text with fewer words than this shares too many trigrams between languages to be
+// routed, ie "nice" reads as German and "terrible" as French
+const MIN_ROUTING_WORDS: usize = 3;
+
+// Longest word that is split into trigrams, longer ones are mostly URLs or keyboard mashing
+const MAX_WORD_CHARS: usize = 40;
+
+lazy_static! {
+    static ref PROFILES: Vec<Profile> = vec![
+        Profile::new("en", include_str!("resources/lang/profiles/en.txt")),
+        Profile::new("es", include_str!("resources/lang/profiles/es.txt")),
+        Profile::new("pt", include_str!("resources/lang/profiles/pt.txt")),
+        Profile::new("de", include_str!("resources/lang/profiles/de.txt")),
+        Profile::new("fr", include_str!("resources/lang/profiles/fr.txt")),
+        Profile::new("it", include_str!("resources/lang/profiles/it.txt")),
+        Profile::new("nl", include_str!("resources/lang/profiles/nl.txt")),
+    ];
+}
+
+// The trigrams of a language, most common first
+struct Profile {
+    code: &'static str,
+    // How much each trigram counts towards the language, more for more common ones
+    weights: HashMap<&'static str, f64>,
+}
+
+impl Profile {
+    fn new(code: &'static str, raw: &'static str) -> Profile {
+        let trigrams: Vec<&str> = raw.lines().filter(|line| !line.is_empty()).collect();
+        let top = (trigrams.len() as f64 + 1.0).ln();
+        let weights = trigrams.iter().enumerate().map(|(rank, &trigram)| (trigram, top - (rank as f64 + 1.0).ln()))
+            .collect();
+        Profile { code, weights }
+    }
+}
+
+/**
+ * The language `detect_language` found for a text
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LanguageGuess {
+    // ISO 639-1 code, ie "it", which may be a language the analyzer has no pack for
+    pub code: &'static str,
+    // How far ahead of the next most likely language the guess is, from 0 to 1. Short texts
+    // and texts that mix languages have low confidence.
+    pub confidence: f64,
+}
+
+impl LanguageGuess {
+    //The guessed language, or None if the analyzer has no pack for it at all
+    pub fn language(&self) -> Option<Language> {
+        Language::from_code(self.code)
+    }
+}
+
+/**
+ * Guesses the language of `text` without any network access. URLs, email addresses and
+ * @mentions are ignored. Returns None for text with too few letters to tell, or with no letter
+ * trigrams that any of the profiles know.
+ **/
+pub fn detect_language(text: &str) -> Option<LanguageGuess> {
+    let words: Vec<&str> = text.split_whitespace().filter(|word| !is_address(word)).collect();
+    let mut latin = 0;
+    let mut scripts: HashMap<&'static str, usize> = HashMap::new();
+    for chr in words.iter().flat_map(|word| word.chars()).filter(|c| c.is_alphabetic()) {
+        match script_language(chr) {
+            Some(code) => *scripts.entry(code).or_insert(0) += 1,
+            None => latin += 1,
+        }
+    }
+    let other: usize = scripts.values().sum();
+    if other > latin {
+        // Kanji are written with kana in Japanese
+        if scripts.contains_key("ja") {
+            let han = scripts.remove("zh").unwrap_or(0);
+            *scripts.entry("ja").or_insert(0) += han;
+        }
+        let (code, count) = scripts.into_iter().max_by_key(|&(code, count)| (count, code))?;
+        return Some(LanguageGuess { code, confidence: count as f64 / (latin + other) as f64 });
+    }
+    if latin + other < MIN_LETTERS {
+        return None;
+    }
+
+    let mut trigrams = HashMap::new();
+    for word in words {
+        count_trigrams(word, &mut trigrams);
+    }
+    let mut scores: Vec<(&'static str, f64)> = PROFILES.iter().map(|profile| {
+        let score = trigrams.iter()
+            .filter_map(|(trigram, &count)| profile.weights.get(trigram.as_str()).map(|weight| weight * count as f64))
+            .sum();
+        (profile.code, score)
+    }).collect();
+    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    let (code, best) = scores[0];
+    if best <= 0.0 {
+        return None;
+    }
+    Some(LanguageGuess { code, confidence: (best - scores[1].1) / best })
+}
+
+//Whether a guess is sure enough for `polarity_scores_auto` to score text as another language.
+//Guesses from the script, like Cyrillic or kana, hold however short the text is.
+pub(crate) fn is_routable(guess: &LanguageGuess, text: &str) -> bool {
+    if !PROFILES.iter().any(|profile| profile.code == guess.code) {
+        return true;
+    }
+    let words = text.split_whitespace()
+        .filter(|word| !is_address(word) && word.chars().any(char::is_alphabetic))
+        .count();
+    words >= MIN_ROUTING_WORDS && guess.confidence >= MIN_ROUTING_CONFIDENCE
+}
+
+// Words that are addresses rather than language, like "https://t.co/x" or "@user"
+fn is_address(word: &str) -> bool {
+    word.contains("://") || word.contains('@') || word.starts_with("www.")
+}
+
+// Counts the trigrams of the lowercased words of text, padded with '_' like the profiles
+fn count_trigrams(text: &str, counts: &mut HashMap<String, usize>) {
+    let mut word: Vec<char> = vec!['_'];
+    let mut trigram = String::new();
+    for chr in text.chars().chain(Some(' ')) {
+        if chr.is_alphabetic() {
+            word.extend(chr.to_lowercase());
+            continue;
+        }
+        if word.len() > 1 && word.len() <= MAX_WORD_CHARS + 1 {
+            word.push('_');
+            for window in word.windows(3) {
+                trigram.clear();
+                trigram.extend(window);
+                if let Some(count) = counts.get_mut(trigram.as_str()) {
+                    *count += 1;
+                } else {
+                    counts.insert(trigram.clone(), 1);
+                }
+            }
+        }
+        word.truncate(1);
+    }
+}
+
+// The language a letter's script is most used for, "und" (undetermined) for scripts without
+// one here, or None for Latin letters
+fn script_language(chr: char) -> Option<&'static str> {
+    let code = match chr as u32 {
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => "el",
+        0x0400..=0x052F => "ru",
+        0x0590..=0x05FF => "he",
+        0x0600..=0x06FF | 0x0750..=0x077F => "ar",
+        0x0900..=0x097F => "hi",
+        0x0E00..=0x0E7F => "th",
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => "ko",
+        0x3040..=0x30FF | 0x31F0..=0x31FF => "ja",
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF => "zh",
+        0x0000..=0x024F | 0x1E00..=0x1EFF => return None,
+        _ => "und",
+    };
+    Some(code)
+}
+
+/**
+ * Scores from `SentimentIntensityAnalyzer::polarity_scores_auto`, with the language they were
+ * scored as
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LanguageScores {
+    pub language: Language,
+    pub scores: SentimentScores,
+    // What detection found, or None if the text was too short to tell. A guess that is too
+    // uncertain to act on is kept here, but the text is scored in the analyzer's own language.
+    pub guess: Option<LanguageGuess>,
+}
+
+/**
+ * `polarity_scores_auto` detected a language it has no lexicon for, so it didn't score the
+ * text rather than return a misleading neutral score
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnsupportedLanguageError {
+    pub guess: LanguageGuess,
+}
+
+impl fmt::Display for UnsupportedLanguageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.guess.language() {
+            Some(language) => write!(f, "unsupported language \"{}\", its cargo feature isn't enabled", language),
+            None => write!(f, "unsupported language \"{}\"", self.guess.code),
+        }
+    }
+}
+
+impl Error for UnsupportedLanguageError {}
@@ -0,0 +1,95 @@
+use is_punctuation;
+use spans::Replacement;
+
+// Letters repeated up to this many times are left alone, as in "good" or "cool"
+const MAX_PLAIN_RUN: usize = 2;
+// Words with more elongated runs than this only try collapsing all of them the same way,
+// rather than every combination
+const MAX_COMBINED_RUNS: usize = 4;
+
+/**
+ * Spellings an elongated word might stand for, most likely first: "sooooo" gives "soo" and
+ * "so", "hahahaha" gives "hahaha", "haha", "hah" and "ha". The case of the word is kept.
+ * Empty if the word isn't elongated.
+ **/
+pub(crate) fn candidates(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut candidates = Vec::new();
+
+    // Runs of the same letter, as (start, length), and which of them are elongated
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=chars.len() {
+        if i == chars.len() || !same_letter(chars[i], chars[start]) {
+            runs.push((start, i - start));
+            start = i;
+        }
+    }
+    let elongated: Vec<usize> = (0..runs.len())
+        .filter(|&r| runs[r].1 > MAX_PLAIN_RUN && chars[runs[r].0].is_alphabetic())
+        .collect();
+    if !elongated.is_empty() {
+        // Each elongated run becomes two letters or one, preferring forms with fewer single letters
+        let count = elongated.len();
+        let mut singles: Vec<Vec<bool>> = if count <= MAX_COMBINED_RUNS {
+            (0..1usize << count).map(|mask| (0..count).map(|e| mask >> e & 1 == 1).collect()).collect()
+        } else {
+            vec![vec![false; count], vec![true; count]]
+        };
+        singles.sort_by_key(|single| single.iter().filter(|&&s| s).count());
+        for single in singles {
+            let mut candidate = String::with_capacity(word.len());
+            for (r, &(start, len)) in runs.iter().enumerate() {
+                let len = match elongated.iter().position(|&e| e == r) {
+                    Some(e) if single[e] => 1,
+                    Some(_) => MAX_PLAIN_RUN,
+                    None => len,
+                };
+                candidate.extend(&chars[start..start + len]);
+            }
+            candidates.push(candidate);
+        }
+    }
+
+    // A syllable repeated three times or more, like "hahaha" or "lololol"
+    let repeats_syllable = chars.len() >= 6 && chars[..2].iter().all(|c| c.is_alphabetic()) &&
+                           !same_letter(chars[0], chars[1]) &&
+                           chars.iter().enumerate().all(|(i, &c)| same_letter(c, chars[i % 2]));
+    if repeats_syllable {
+        for &len in &[6, 4, 3, 2] {
+            if len < chars.len() {
+                candidates.push(chars[..len].iter().collect());
+            }
+        }
+    }
+    candidates
+}
+
+fn same_letter(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/**
+ * Copies text into result, replacing every elongated word that isn't known itself with its
+ * first known spelling from `candidates`. Punctuation around a word is kept, so "soooo!!!"
+ * still ends in three '!'. Words without a known spelling are left alone.
+ **/
+pub(crate) fn normalize_into<F: Fn(&str) -> bool>(text: &str, is_known: F, replacements: &mut Vec<Replacement>,
+                                                  result: &mut String) {
+    let mut copied = 0;
+    for word in text.split_whitespace() {
+        let core = word.trim_matches(is_punctuation);
+        if core.is_empty() || is_known(core) {
+            continue;
+        }
+        if let Some(spelling) = candidates(core).into_iter().find(|candidate| is_known(candidate)) {
+            let start = core.as_ptr() as usize - text.as_ptr() as usize;
+            result.push_str(&text[copied..start]);
+            let rewritten_start = result.len();
+            result.push_str(&spelling);
+            replacements.push(Replacement { rewritten: rewritten_start..result.len(), original: start..start + core.len() });
+            copied = start + core.len();
+        }
+    }
+    result.push_str(&text[copied..]);
+}
@@ -0,0 +1,121 @@
+use idioms::IdiomMatch;
+use scores::SentimentScores;
+use spans::Span;
+
+/**
+ * The heuristics that can change the valence of a token
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Rule {
+    // The token is a booster or part of "kind of", so its own valence is ignored
+    Modifier,
+    // The token is ALL CAPS in text that also has non-caps words
+    CapsEmphasis,
+    // The token was elongated, like "goooood"
+    Elongation,
+    // A booster or dampener ("very", "kinda", ...) appears within three tokens before
+    Booster,
+    // A negation ("not", "isn't", ...) appears within three tokens before
+    Negation,
+    // "never so" or "never this" before the token intensifies it
+    NeverIntensifier,
+    // A special case idiom ("the bomb", "bad ass", ...) overrides the valence
+    SpecialIdiom,
+    // The token is part of a sentiment laden idiom ("under the weather", ...)
+    LadenIdiom,
+    // "least" directly before the token flips it
+    Least,
+    // The token comes before (dampened) or after (emphasized) a contrastive "but"
+    ButContrast,
+    // A rule added to the analyzer's `RulePipeline` outside this crate
+    Custom,
+}
+
+/**
+ * A single change to a token's valence, in the order it was applied
+ **/
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Adjustment {
+    pub rule: Rule,
+    // Index of the token that triggered the rule, if there is a single one
+    pub trigger: Option<usize>,
+    pub before: f64,
+    pub after: f64,
+}
+
+/**
+ * How a single token from the tokenized text was scored
+ **/
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TokenTrace {
+    pub token: String,
+    // Where the token is in the analyzed text
+    pub span: Span,
+    // Whether the tokenizer found the token to be an emoticon
+    pub is_emoticon: bool,
+    // Valence of the token in the lexicon, or None if it isn't a lexicon word
+    pub base_valence: Option<f64>,
+    pub adjustments: Vec<Adjustment>,
+    // Valence the token contributed to the final score
+    pub valence: f64,
+}
+
+/**
+ * How the sum of token valences was turned into the compound score
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Normalization {
+    // Sum of all token valences
+    pub raw_total: f64,
+    // raw_total pushed away from zero by the punctuation amplifier
+    pub amplified_total: f64,
+    pub alpha: f64,
+    pub compound: f64,
+}
+
+/**
+ * Full breakdown of a call to `polarity_scores`
+ **/
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Explanation {
+    pub tokens: Vec<TokenTrace>,
+    // Sentiment laden idioms found in the tokens
+    pub idioms: Vec<IdiomMatch>,
+    // Emphasis added for '!' and '?' in the text
+    pub punctuation_amplifier: f64,
+    pub normalization: Normalization,
+    pub scores: SentimentScores,
+}
+
+impl TokenTrace {
+    pub(crate) fn new(token: &str, base_valence: Option<f64>) -> TokenTrace {
+        TokenTrace {
+            token: token.to_string(),
+            span: Span::default(),
+            is_emoticon: false,
+            base_valence,
+            adjustments: Vec::new(),
+            valence: 0f64,
+        }
+    }
+}
+
+/**
+ * Collects adjustments while scoring a token. Scoring without an explanation passes `None`,
+ * so the normal path doesn't pay for the bookkeeping.
+ **/
+pub(crate) type Log<'l> = Option<&'l mut Vec<Adjustment>>;
+
+pub(crate) fn record(log: &mut Log, rule: Rule, trigger: Option<usize>, before: f64, after: f64) {
+    if let Some(ref mut adjustments) = *log {
+        if before != after {
+            adjustments.push(Adjustment { rule, trigger, before, after });
+        }
+    }
+}
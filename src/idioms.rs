@@ -0,0 +1,147 @@
+use std::io::Read;
+use std::path::Path;
+use unicase::UniCase;
+
+use lexicon::{LexiconError, LexiconErrorKind};
+use tables::{for_each_line, parse_valence, split_pair};
+use ParsedText;
+
+// sentiment laden idioms that do not contain lexicon words
+static SENTIMENT_LADEN_IDIOMS: &[(&str, f64)] = &[
+     ("cut the mustard", 2.0), ("hand to mouth", -2.0),
+     ("back handed", -2.0), ("blow smoke", -2.0), ("blowing smoke", -2.0),
+     ("upper hand", 1.0), ("break a leg", 2.0),
+     ("cooking with gas", 2.0), ("in the black", 2.0), ("in the red", -2.0),
+     ("on the ball", 2.0), ("under the weather", -2.0)];
+
+/**
+ * Multi-word phrases that carry sentiment as a whole, like "under the weather". When a phrase
+ * appears in the text, its valence replaces the valences of the words in it.
+ *
+ * `IdiomTable::default()` holds the idioms from the original implementation.
+ *
+ * Files have one `phrase<TAB>valence` pair per line.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdiomTable {
+    idioms: Vec<Idiom>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Idiom {
+    phrase: String,
+    // The phrase as the tokenizer sees it, so "break a leg" is matched as "break leg"
+    words: Vec<String>,
+    valence: f64,
+}
+
+/**
+ * An idiom found in a text, spanning the tokens `start..end`
+ **/
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IdiomMatch {
+    pub phrase: String,
+    pub start: usize,
+    pub end: usize,
+    pub valence: f64,
+}
+
+impl IdiomTable {
+    pub fn empty() -> IdiomTable {
+        IdiomTable { idioms: Vec::new() }
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<IdiomTable, LexiconError> {
+        let mut table = IdiomTable::empty();
+        for_each_line(reader, |line, line_no| {
+            let (phrase, valence) = split_pair(line, line_no)?;
+            let valence = parse_valence(valence, line_no, phrase.chars().count() + 2)?;
+            if table.insert(phrase, valence).is_some() {
+                return Err(LexiconError::new(line_no, 1, LexiconErrorKind::DuplicateEntry(phrase.to_string())));
+            }
+            Ok(())
+        })?;
+        Ok(table)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<IdiomTable, LexiconError> {
+        let file = ::std::fs::File::open(path).map_err(|e| LexiconError::new(0, 0, LexiconErrorKind::Io(e)))?;
+        IdiomTable::from_reader(file)
+    }
+
+    //Adds or replaces an idiom, returning its previous valence
+    pub fn insert<S: Into<String>>(&mut self, phrase: S, valence: f64) -> Option<f64> {
+        let phrase = phrase.into();
+        let previous = self.remove(&phrase);
+        let words: Vec<String> = ParsedText::tokenize(&phrase).iter().map(|w| w.to_string()).collect();
+        if !words.is_empty() {
+            self.idioms.push(Idiom { phrase, words, valence });
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, phrase: &str) -> Option<f64> {
+        let position = self.idioms.iter().position(|idiom| UniCase::new(phrase) == UniCase::new(&idiom.phrase));
+        position.map(|i| self.idioms.remove(i).valence)
+    }
+
+    pub fn get(&self, phrase: &str) -> Option<f64> {
+        self.idioms.iter()
+            .find(|idiom| UniCase::new(phrase) == UniCase::new(&idiom.phrase))
+            .map(|idiom| idiom.valence)
+    }
+
+    pub fn len(&self) -> usize {
+        self.idioms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.idioms.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.idioms.iter().map(|idiom| (idiom.phrase.as_str(), idiom.valence))
+    }
+
+    //Finds the idioms in a token stream, preferring the longest idiom where several overlap
+    pub(crate) fn find_matches(&self, tokens: &[UniCase<&str>]) -> Vec<IdiomMatch> {
+        let mut matches = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let longest = self.idioms.iter()
+                .filter(|idiom| idiom.matches_at(tokens, i))
+                .max_by_key(|idiom| idiom.words.len());
+            match longest {
+                Some(idiom) => {
+                    matches.push(IdiomMatch {
+                        phrase: idiom.phrase.clone(),
+                        start: i,
+                        end: i + idiom.words.len(),
+                        valence: idiom.valence,
+                    });
+                    i += idiom.words.len();
+                },
+                None => i += 1,
+            }
+        }
+        matches
+    }
+}
+
+impl Idiom {
+    fn matches_at(&self, tokens: &[UniCase<&str>], start: usize) -> bool {
+        tokens.len() >= start + self.words.len() &&
+            self.words.iter().zip(&tokens[start..]).all(|(word, token)| UniCase::new(word.as_str()) == *token)
+    }
+}
+
+impl Default for IdiomTable {
+    fn default() -> IdiomTable {
+        let mut table = IdiomTable::empty();
+        for &(phrase, valence) in SENTIMENT_LADEN_IDIOMS {
+            table.insert(phrase, valence);
+        }
+        table
+    }
+}
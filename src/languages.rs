@@ -0,0 +1,202 @@
+/*!
+ * Word tables for scoring text in languages other than English. English is always built in;
+ * the other packs are compiled in by the `spanish`, `portuguese`, `german` and `french` cargo
+ * features, or all of them by `all-languages`, so English-only builds don't carry their tables.
+ *
+ * The lexicons in `resources/lang` translate common sentiment words from vader_lexicon.txt and
+ * keep the valence of the English word, see `resources/lang/build_lexicons.py`.
+ **/
+
+use std::fmt;
+use std::sync::Arc;
+
+use idioms::IdiomTable;
+use lexicon::Lexicon;
+use rules::RulePipeline;
+use tables::{BoosterTable, ContrastTable, NegationTable, SpecialIdiomTable};
+use {DEFAULT_BOOSTERS, DEFAULT_CONTRASTS, DEFAULT_IDIOMS, DEFAULT_LEXICON, DEFAULT_NEGATIONS, DEFAULT_RULES,
+     DEFAULT_SPECIAL_IDIOMS};
+
+lazy_static! {
+    static ref ENGLISH: LanguagePack = LanguagePack {
+        language: Language::English,
+        lexicon: &DEFAULT_LEXICON,
+        idioms: DEFAULT_IDIOMS.clone(),
+        special_idioms: DEFAULT_SPECIAL_IDIOMS.clone(),
+        boosters: DEFAULT_BOOSTERS.clone(),
+        negations: DEFAULT_NEGATIONS.clone(),
+        contrasts: DEFAULT_CONTRASTS.clone(),
+        rules: DEFAULT_RULES.clone(),
+    };
+}
+
+// Declares the lexicon and pack of a language from its directory in resources/lang
+macro_rules! language_pack {
+    ($feature:tt, $language:expr, $lexicon:ident, $pack:ident, $dir:expr) => {
+        #[cfg(feature = $feature)]
+        lazy_static! {
+            static ref $lexicon: Lexicon = Lexicon::from_static_str(
+                include_str!(concat!("resources/lang/", $dir, "/lexicon.txt")), ::lexicon::Repeats::Replace)
+                .expect(concat!($dir, "/lexicon.txt is well formed"));
+            static ref $pack: LanguagePack = LanguagePack::load(
+                $language, &$lexicon,
+                include_str!(concat!("resources/lang/", $dir, "/boosters.txt")),
+                include_str!(concat!("resources/lang/", $dir, "/negations.txt")),
+                include_str!(concat!("resources/lang/", $dir, "/contrasts.txt")));
+        }
+    };
+}
+
+language_pack!("spanish", Language::Spanish, SPANISH_LEXICON, SPANISH, "es");
+language_pack!("portuguese", Language::Portuguese, PORTUGUESE_LEXICON, PORTUGUESE, "pt");
+language_pack!("german", Language::German, GERMAN_LEXICON, GERMAN, "de");
+language_pack!("french", Language::French, FRENCH_LEXICON, FRENCH, "fr");
+
+/**
+ * A language with word tables the analyzer can score it with, if its pack is compiled in
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Language {
+    English,
+    Spanish,
+    Portuguese,
+    German,
+    French,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] = [Language::English, Language::Spanish, Language::Portuguese,
+                                    Language::German, Language::French];
+
+    //ISO 639-1 code, ie "es"
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::Portuguese => "pt",
+            Language::German => "de",
+            Language::French => "fr",
+        }
+    }
+
+    //Parses an ISO 639-1 code, ignoring case and any region, ie "pt-BR" -> Portuguese
+    pub fn from_code(code: &str) -> Option<Language> {
+        let primary = code.split(&['-', '_'][..]).next().unwrap_or("");
+        Language::ALL.iter().cloned().find(|language| language.code().eq_ignore_ascii_case(primary))
+    }
+
+    /**
+     * The word tables for the language, or None if its cargo feature isn't enabled
+     **/
+    pub fn pack(&self) -> Option<&'static LanguagePack> {
+        match *self {
+            Language::English => Some(&ENGLISH),
+            #[cfg(feature = "spanish")]
+            Language::Spanish => Some(&SPANISH),
+            #[cfg(feature = "portuguese")]
+            Language::Portuguese => Some(&PORTUGUESE),
+            #[cfg(feature = "german")]
+            Language::German => Some(&GERMAN),
+            #[cfg(feature = "french")]
+            Language::French => Some(&FRENCH),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.pack().is_some()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/**
+ * The lexicon, word tables and rules that score one language. Give it to
+ * `SentimentIntensityAnalyzerBuilder::language_pack`, or use
+ * `SentimentIntensityAnalyzer::for_language`.
+ *
+ * Packs other than English have no idioms, and leave out the rules that only make sense for
+ * English words: "no", "no_negation", "never_intensifier" and "least". Their own word for "no"
+ * is a negation instead.
+ **/
+pub struct LanguagePack {
+    language: Language,
+    lexicon: &'static Lexicon,
+    pub(crate) idioms: Arc<IdiomTable>,
+    pub(crate) special_idioms: Arc<SpecialIdiomTable>,
+    pub(crate) boosters: Arc<BoosterTable>,
+    pub(crate) negations: Arc<NegationTable>,
+    pub(crate) contrasts: Arc<ContrastTable>,
+    pub(crate) rules: Arc<RulePipeline>,
+}
+
+impl LanguagePack {
+    #[cfg(any(feature = "spanish", feature = "portuguese", feature = "german", feature = "french"))]
+    fn load(language: Language, lexicon: &'static Lexicon, boosters: &str, negations: &str,
+            contrasts: &str) -> LanguagePack {
+        let mut rules = RulePipeline::default();
+        for name in &["no", "no_negation", "never_intensifier", "least"] {
+            rules.remove(name);
+        }
+        LanguagePack {
+            language,
+            lexicon,
+            idioms: Arc::new(IdiomTable::empty()),
+            special_idioms: Arc::new(SpecialIdiomTable::empty()),
+            boosters: Arc::new(BoosterTable::from_reader(boosters.as_bytes()).expect("boosters.txt is well formed")),
+            negations: Arc::new(NegationTable::from_reader(negations.as_bytes())
+                .expect("negations.txt is well formed")),
+            contrasts: Arc::new(ContrastTable::from_reader(contrasts.as_bytes())
+                .expect("contrasts.txt is well formed")),
+            rules: Arc::new(rules),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn lexicon(&self) -> &'static Lexicon {
+        self.lexicon
+    }
+
+    pub fn idioms(&self) -> &IdiomTable {
+        &self.idioms
+    }
+
+    pub fn special_idioms(&self) -> &SpecialIdiomTable {
+        &self.special_idioms
+    }
+
+    pub fn boosters(&self) -> &BoosterTable {
+        &self.boosters
+    }
+
+    pub fn negations(&self) -> &NegationTable {
+        &self.negations
+    }
+
+    pub fn contrasts(&self) -> &ContrastTable {
+        &self.contrasts
+    }
+
+    pub fn rules(&self) -> &RulePipeline {
+        &self.rules
+    }
+}
+
+impl fmt::Debug for LanguagePack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LanguagePack")
+            .field("language", &self.language)
+            .field("lexicon_words", &self.lexicon.len())
+            .finish()
+    }
+}
@@ -0,0 +1,430 @@
+use std::borrow::Cow;
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::collections::hash_map::{self, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::iter::FromIterator;
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use words::{Word, WordRef};
+
+/**
+ * A word's sentiment as rated by human judges. `mean` is the valence used for scoring.
+ * `std_dev` and `ratings` are only known for lexicons in the vader_lexicon.txt format.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexiconEntry {
+    pub mean: f64,
+    pub std_dev: Option<f64>,
+    pub ratings: Vec<i8>,
+}
+
+impl LexiconEntry {
+    pub fn new(mean: f64) -> LexiconEntry {
+        LexiconEntry { mean, std_dev: None, ratings: Vec::new() }
+    }
+
+    //Computes the mean and (population) standard deviation of a list of ratings
+    pub fn from_ratings(ratings: Vec<i8>) -> LexiconEntry {
+        if ratings.is_empty() {
+            return LexiconEntry::new(0f64);
+        }
+        let count = ratings.len() as f64;
+        let mean = ratings.iter().map(|&r| f64::from(r)).sum::<f64>() / count;
+        let variance = ratings.iter().map(|&r| (f64::from(r) - mean).powi(2)).sum::<f64>() / count;
+        LexiconEntry { mean, std_dev: Some(variance.sqrt()), ratings }
+    }
+}
+
+// vader_lexicon.txt has emoticons in several cases with different valences, like ":p" and
+// ":P". vaderSentiment looks words up lowercased, so only the lowercase spelling is ever scored.
+fn is_shadowed_spelling(word: &str, existing: &str) -> bool {
+    existing != word && existing == existing.to_lowercase() && word != word.to_lowercase()
+}
+
+// What loading a word that is already in the lexicon does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Repeats {
+    // Fail with `DuplicateEntry` if the valence differs
+    Reject,
+    // Replace the earlier entry
+    Replace,
+    // Replace the earlier entry, unless an uppercase spelling would replace a lowercase one
+    Python,
+}
+
+/**
+ * A sentiment lexicon that owns its words, for lexicons that are loaded at runtime.
+ *
+ * Each line holds a word and its mean valence separated by a tab. Lines may either follow the
+ * four column format of vader_lexicon.txt (word, mean, standard deviation, raw ratings) or
+ * just have the first two columns. Words are matched case-insensitively.
+ **/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lexicon {
+    words: HashMap<Word, LexiconEntry>,
+}
+
+impl Lexicon {
+    pub fn new() -> Lexicon {
+        Lexicon::default()
+    }
+
+    /**
+     * Fails with `DuplicateEntry` when a word is repeated with a different valence, which in a
+     * hand written lexicon is most likely a mistake. Exact repeats are allowed.
+     **/
+    pub fn from_reader<R: Read>(reader: R) -> Result<Lexicon, LexiconError> {
+        Lexicon::read_lines(reader, Repeats::Reject)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Lexicon, LexiconError> {
+        let file = File::open(path).map_err(|e| LexiconError::new(0, 0, LexiconErrorKind::Io(e)))?;
+        Lexicon::from_reader(file)
+    }
+
+    /**
+     * Loads a lexicon the way the embedded vader_lexicon.txt is loaded: a word that is repeated
+     * replaces its earlier entry. This reads the reference vader_lexicon.txt as is, which
+     * repeats a few words.
+     **/
+    pub fn from_reader_lenient<R: Read>(reader: R) -> Result<Lexicon, LexiconError> {
+        Lexicon::read_lines(reader, Repeats::Replace)
+    }
+
+    pub fn from_path_lenient<P: AsRef<Path>>(path: P) -> Result<Lexicon, LexiconError> {
+        let file = File::open(path).map_err(|e| LexiconError::new(0, 0, LexiconErrorKind::Io(e)))?;
+        Lexicon::from_reader_lenient(file)
+    }
+
+    /**
+     * Loads a lexicon the way vaderSentiment 3.3.2 does, for analyzers in `Compat::Python` mode.
+     * Like `from_reader_lenient`, except that an uppercase spelling never replaces a lowercase
+     * one, as the original only looks up lowercased words.
+     **/
+    pub fn from_reader_python<R: Read>(reader: R) -> Result<Lexicon, LexiconError> {
+        Lexicon::read_lines(reader, Repeats::Python)
+    }
+
+    pub fn from_path_python<P: AsRef<Path>>(path: P) -> Result<Lexicon, LexiconError> {
+        let file = File::open(path).map_err(|e| LexiconError::new(0, 0, LexiconErrorKind::Io(e)))?;
+        Lexicon::from_reader_python(file)
+    }
+
+    fn read_lines<R: Read>(reader: R, repeats: Repeats) -> Result<Lexicon, LexiconError> {
+        let mut lexicon = Lexicon::new();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|e| LexiconError::new(i + 1, 1, LexiconErrorKind::Io(e)))?;
+            if let Some((word, entry)) = parse_line(Cow::Owned(line), i + 1)? {
+                lexicon.insert_parsed(word, entry, i + 1, repeats)?;
+            }
+        }
+        Ok(lexicon)
+    }
+
+    // Parses one of the embedded lexicons without copying its words
+    pub(crate) fn from_static_str(raw: &'static str, repeats: Repeats) -> Result<Lexicon, LexiconError> {
+        let mut lexicon = Lexicon::new();
+        for (i, line) in raw.lines().enumerate() {
+            if let Some((word, entry)) = parse_line(Cow::Borrowed(line), i + 1)? {
+                lexicon.insert_parsed(word, entry, i + 1, repeats)?;
+            }
+        }
+        Ok(lexicon)
+    }
+
+    // Adds a parsed line, handling a word that is already there, in any case, as `repeats` says
+    fn insert_parsed(&mut self, word: Cow<'static, str>, entry: LexiconEntry, line_no: usize, repeats: Repeats) -> Result<(), LexiconError> {
+        if let Some((existing, previous)) = self.words.get_key_value(WordRef::new(&word)) {
+            match repeats {
+                Repeats::Reject if previous.mean != entry.mean => {
+                    return Err(LexiconError::new(line_no, 1, LexiconErrorKind::DuplicateEntry(word.into_owned())));
+                },
+                Repeats::Python if is_shadowed_spelling(&word, &existing.0) => return Ok(()),
+                _ => {},
+            }
+        }
+        self.words.remove(WordRef::new(&word));
+        self.words.insert(Word(word), entry);
+        Ok(())
+    }
+
+    //Mean valence of a word
+    pub fn get(&self, word: &str) -> Option<f64> {
+        self.words.get(WordRef::new(word)).map(|entry| entry.mean)
+    }
+
+    pub fn entry(&self, word: &str) -> Option<&LexiconEntry> {
+        self.words.get(WordRef::new(word))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains_key(WordRef::new(word))
+    }
+
+    //Adds or replaces a word with only a mean valence, returning its previous valence
+    pub fn insert<S: Into<String>>(&mut self, word: S, valence: f64) -> Option<f64> {
+        self.insert_entry(word, LexiconEntry::new(valence)).map(|entry| entry.mean)
+    }
+
+    pub fn insert_entry<S: Into<String>>(&mut self, word: S, entry: LexiconEntry) -> Option<LexiconEntry> {
+        let word = word.into();
+        let previous = self.words.remove(WordRef::new(&word));
+        self.words.insert(Word(Cow::Owned(word)), entry);
+        previous
+    }
+
+    pub fn remove(&mut self, word: &str) -> Option<LexiconEntry> {
+        self.words.remove(WordRef::new(word))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    //Iterates over words and their mean valences
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { inner: self.words.iter() }
+    }
+
+    pub fn entries(&self) -> Entries<'_> {
+        Entries { inner: self.words.iter() }
+    }
+}
+
+// Parses a single "word\tmean[\tstd_dev\tratings]" line. Blank lines are skipped.
+fn parse_line(line: Cow<'static, str>, line_no: usize) -> Result<Option<(Cow<'static, str>, LexiconEntry)>, LexiconError> {
+    let text = line.trim_end_matches('\r');
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let columns: Vec<&str> = text.split('\t').collect();
+    if columns.len() == 1 {
+        return Err(LexiconError::new(line_no, text.chars().count() + 1, LexiconErrorKind::MissingTab));
+    } else if columns.len() != 2 && columns.len() != 4 {
+        return Err(LexiconError::new(line_no, 1, LexiconErrorKind::UnexpectedColumns(columns.len())));
+    }
+    // 1-based character column where each field starts
+    let column = |field: usize| columns[..field].iter().map(|c| c.chars().count() + 1).sum::<usize>() + 1;
+
+    let mean: f64 = columns[1].trim().parse().map_err(|_|
+        LexiconError::new(line_no, column(1), LexiconErrorKind::InvalidValence(columns[1].to_string())))?;
+    let mut entry = LexiconEntry::new(mean);
+    if columns.len() == 4 {
+        let std_dev: f64 = columns[2].trim().parse().map_err(|_|
+            LexiconError::new(line_no, column(2), LexiconErrorKind::InvalidStdDev(columns[2].to_string())))?;
+        entry.std_dev = Some(std_dev);
+        entry.ratings = parse_ratings(columns[3]).ok_or_else(||
+            LexiconError::new(line_no, column(3), LexiconErrorKind::InvalidRatings(columns[3].to_string())))?;
+    }
+
+    let word_len = columns[0].len();
+    let word = match line {
+        Cow::Borrowed(line) => Cow::Borrowed(&line[..word_len]),
+        Cow::Owned(mut line) => {
+            line.truncate(word_len);
+            Cow::Owned(line)
+        }
+    };
+    Ok(Some((word, entry)))
+}
+
+// Parses a list of ratings like "[-1, 0, 2]"
+fn parse_ratings(raw: &str) -> Option<Vec<i8>> {
+    let raw = raw.trim();
+    if !raw.starts_with('[') || !raw.ends_with(']') {
+        return None;
+    }
+    let inner = raw[1..raw.len() - 1].trim();
+    if inner.is_empty() {
+        return Some(Vec::new());
+    }
+    inner.split(',').map(|rating| rating.trim().parse().ok()).collect()
+}
+
+impl<S: Into<String>> FromIterator<(S, f64)> for Lexicon {
+    fn from_iter<I: IntoIterator<Item = (S, f64)>>(words: I) -> Lexicon {
+        let mut lexicon = Lexicon::new();
+        lexicon.extend(words);
+        lexicon
+    }
+}
+
+impl<S: Into<String>> Extend<(S, f64)> for Lexicon {
+    fn extend<I: IntoIterator<Item = (S, f64)>>(&mut self, words: I) {
+        for (word, valence) in words {
+            self.insert(word, valence);
+        }
+    }
+}
+
+impl FromStr for Lexicon {
+    type Err = LexiconError;
+
+    fn from_str(raw: &str) -> Result<Lexicon, LexiconError> {
+        Lexicon::from_reader(raw.as_bytes())
+    }
+}
+
+/**
+ * Iterates over the words of a `Lexicon` and their mean valences, in no particular order
+ **/
+pub struct Iter<'a> {
+    inner: hash_map::Iter<'a, Word, LexiconEntry>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, f64);
+
+    fn next(&mut self) -> Option<(&'a str, f64)> {
+        self.inner.next().map(|(word, entry)| (word.0.as_ref(), entry.mean))
+    }
+}
+
+/**
+ * Iterates over the words of a `Lexicon` and their full entries, in no particular order
+ **/
+pub struct Entries<'a> {
+    inner: hash_map::Iter<'a, Word, LexiconEntry>,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = (&'a str, &'a LexiconEntry);
+
+    fn next(&mut self) -> Option<(&'a str, &'a LexiconEntry)> {
+        self.inner.next().map(|(word, entry)| (word.0.as_ref(), entry))
+    }
+}
+
+/**
+ * A lexicon is serialized as a map from words to entries, sorted by word so the output is
+ * stable. Entries with only a mean are written as a bare number, so the `lexicon` dict of the
+ * Python analyzer can be loaded as is; rated entries are `{"mean", "std_dev", "ratings"}`.
+ **/
+#[cfg(feature = "serde")]
+impl Serialize for Lexicon {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let words: BTreeMap<&str, &LexiconEntry> = self.entries().collect();
+        words.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Lexicon {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Lexicon, D::Error> {
+        // Sorted, so of two spellings that differ only by case the lowercase one comes last and wins
+        let words: BTreeMap<String, LexiconEntry> = Deserialize::deserialize(deserializer)?;
+        let mut lexicon = Lexicon::new();
+        for (word, entry) in words {
+            lexicon.insert_entry(word, entry);
+        }
+        Ok(lexicon)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum EntryRepr {
+    Mean(f64),
+    Rated {
+        mean: f64,
+        #[serde(default)]
+        std_dev: Option<f64>,
+        #[serde(default)]
+        ratings: Vec<i8>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LexiconEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = if self.std_dev.is_none() && self.ratings.is_empty() {
+            EntryRepr::Mean(self.mean)
+        } else {
+            EntryRepr::Rated { mean: self.mean, std_dev: self.std_dev, ratings: self.ratings.clone() }
+        };
+        repr.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LexiconEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LexiconEntry, D::Error> {
+        Ok(match EntryRepr::deserialize(deserializer)? {
+            EntryRepr::Mean(mean) => LexiconEntry::new(mean),
+            EntryRepr::Rated { mean, std_dev, ratings } => LexiconEntry { mean, std_dev, ratings },
+        })
+    }
+}
+
+/**
+ * Why a lexicon or word table couldn't be loaded, and where. `line` and `column` are 1-based; both are 0
+ * when the file couldn't be opened at all.
+ **/
+#[derive(Debug)]
+pub struct LexiconError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: LexiconErrorKind,
+}
+
+#[derive(Debug)]
+pub enum LexiconErrorKind {
+    Io(io::Error),
+    // The line has a word but no tab separated valence after it
+    MissingTab,
+    // The valence column isn't a number
+    InvalidValence(String),
+    // The standard deviation column isn't a number
+    InvalidStdDev(String),
+    // The ratings column isn't a list of integers like "[1, -2, 0]"
+    InvalidRatings(String),
+    // The line has the wrong number of tab separated columns
+    UnexpectedColumns(usize),
+    // The same word appears on an earlier line with a different valence
+    DuplicateEntry(String),
+}
+
+impl LexiconError {
+    pub(crate) fn new(line: usize, column: usize, kind: LexiconErrorKind) -> LexiconError {
+        LexiconError { line, column, kind }
+    }
+}
+
+impl fmt::Display for LexiconError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}, column {}: ", self.line, self.column)?;
+        }
+        match self.kind {
+            LexiconErrorKind::Io(ref e) => write!(f, "{}", e),
+            LexiconErrorKind::MissingTab => write!(f, "expected a tab between the word and its valence"),
+            LexiconErrorKind::InvalidValence(ref valence) => write!(f, "valence \"{}\" is not a number", valence),
+            LexiconErrorKind::InvalidStdDev(ref std_dev) => write!(f, "standard deviation \"{}\" is not a number", std_dev),
+            LexiconErrorKind::InvalidRatings(ref ratings) => write!(f, "ratings \"{}\" are not a list of integers", ratings),
+            LexiconErrorKind::UnexpectedColumns(count) => write!(f, "unexpected number of columns ({})", count),
+            LexiconErrorKind::DuplicateEntry(ref word) => write!(f, "duplicate entry for \"{}\"", word),
+        }
+    }
+}
+
+impl Error for LexiconError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.kind {
+            LexiconErrorKind::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
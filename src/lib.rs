@@ -1,45 +1,96 @@
-/**
+/*!
  * If you use the VADER sentiment analysis tools, please cite:
  * Hutto, C.J. & Gilbert, E.E. (2014). VADER: A Parsimonious Rule-based Model for
  * Sentiment Analysis of Social Media Text. Eighth International Conference on
  * Weblogs and Social Media (ICWSM-14). Ann Arbor, MI, June 2014.
  **/
 
-
 #[macro_use] extern crate maplit;
 #[macro_use] extern crate lazy_static;
 extern crate regex;
 extern crate unicase;
+extern crate unicode_segmentation;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+#[macro_use] extern crate serde;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(all(test, feature = "wasm", target_arch = "wasm32"))]
+extern crate wasm_bindgen_test;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 use std::cmp::min;
 use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::ops::Range;
+use std::ptr;
+use std::sync::Arc;
 use regex::Regex;
 use unicase::UniCase;
+use unicode_segmentation::UnicodeSegmentation;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 #[cfg(test)]
 mod tests;
 
-//empirically derived constants for scaling/amplifying sentiments
-const B_INCR: f64 =  0.293;
-const B_DECR: f64 = -0.293;
+mod config;
+mod detect;
+mod elongation;
+mod explain;
+mod idioms;
+mod languages;
+mod lexicon;
+pub mod rules;
+mod scores;
+mod sentences;
+mod social;
+mod spans;
+mod tables;
+mod tokenizer;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod words;
 
-const C_INCR:   f64 =  0.733;
-const NEGATION_SCALAR: f64 = -0.740;
+pub use config::{Compat, VaderConfig};
+pub use detect::{detect_language, LanguageGuess, LanguageScores, UnsupportedLanguageError};
+pub use explain::{Adjustment, Explanation, Normalization, Rule, TokenTrace};
+pub use idioms::{IdiomMatch, IdiomTable};
+pub use languages::{Language, LanguagePack};
+pub use lexicon::{Entries as LexiconEntries, Iter as LexiconIter, Lexicon, LexiconEntry, LexiconError,
+                  LexiconErrorKind};
+pub use rules::{RuleContext, RulePipeline, Stage, TextValences, ValenceRule, WordValence};
+pub use scores::{LabelThresholds, MissingScoreError, SentimentLabel, SentimentScores, Uncertainty};
+pub use sentences::{split_sentences, DocumentScores, Sentence, SentenceScores};
+pub use spans::Span;
+pub use tables::{Booster, BoosterIter, BoosterTable, ContrastTable, NegationTable, SpecialIdiomTable};
+pub use tokenizer::{DefaultTokenizer, Token, Tokenizer};
 
-//sentiment increases for text with question or exclamation marks
-const QMARK_INCR: f64 = 0.180;
-const EMARK_INCR: f64 = 0.292;
+use explain::{record, Log};
+use lexicon::Repeats;
+use rules::{TextValences as Valences, WordValence as Word};
+use social::SocialMediaTokenizer;
+use spans::{Replacement, OffsetMap};
+use tokenizer::PythonTokenizer;
 
-//Maximum amount of question or question marks before their contribution to sentiment is
-//disregarded
-const MAX_EMARK: i32 = 4;
-const MAX_QMARK: i32 = 3;
-const MAX_QMARK_INCR: f64 = 0.96;
+use Booster::{Decr, Incr};
 
-const NORMALIZATION_ALPHA: f64 = 15.0;
+static RAW_LEXICON: &str = include_str!("resources/vader_lexicon.txt");
+static RAW_EMOJI_LEXICON: &str = include_str!("resources/emoji_utf8_lexicon.txt");
 
-static RAW_LEXICON: &'static str = include_str!("resources/vader_lexicon.txt");
-static RAW_EMOJI_LEXICON: &'static str = include_str!("resources/emoji_utf8_lexicon.txt");
+// check for special case idioms containing lexicon words
+static SPECIAL_CASE_IDIOMS: &[(&str, f64)] = &[
+     ("the shit", 3.0), ("the bomb", 3.0), ("bad ass", 1.5), ("badass", 1.5), ("yeah right", -2.0),
+     ("kiss of death", -1.5), ("to die for", 3.0)];
+
+// Special case idioms that only vaderSentiment 3.3.2 has, checked in `Compat::Python` and
+// `Compat::Corrected` modes
+static PYTHON_SPECIAL_CASE_IDIOMS: &[(&str, f64)] = &[
+     ("bus stop", 0.0), ("beating heart", 3.1), ("broken heart", -2.9)];
 
 lazy_static! {
 
@@ -53,58 +104,55 @@ lazy_static! {
         "oughtn't", "shan't", "shouldn't", "uh-uh", "wasn't", "weren't",
         "without", "wont", "wouldnt", "won't", "wouldn't", "rarely", "seldom", "despite"));
 
-    static ref BOOSTER_DICT: HashMap<UniCase<&'static str>, f64> =  convert_args!(hashmap!(
-         "absolutely"=> B_INCR, "amazingly"=> B_INCR, "awfully"=> B_INCR,
-          "completely"=> B_INCR, "considerable"=> B_INCR, "considerably"=> B_INCR,
-          "decidedly"=> B_INCR, "deeply"=> B_INCR, "effing"=> B_INCR, "enormous"=> B_INCR, "enormously"=> B_INCR,
-          "entirely"=> B_INCR, "especially"=> B_INCR, "exceptional"=> B_INCR, "exceptionally"=> B_INCR,
-          "extreme"=> B_INCR, "extremely"=> B_INCR,
-          "fabulously"=> B_INCR, "flipping"=> B_INCR, "flippin"=> B_INCR, "frackin"=> B_INCR, "fracking"=> B_INCR,
-          "fricking"=> B_INCR, "frickin"=> B_INCR, "frigging"=> B_INCR, "friggin"=> B_INCR, "fully"=> B_INCR,
-          "fuckin"=> B_INCR, "fucking"=> B_INCR, "fuggin"=> B_INCR, "fugging"=> B_INCR,
-          "greatly"=> B_INCR, "hella"=> B_INCR, "highly"=> B_INCR, "hugely"=> B_INCR,
-          "incredible"=> B_INCR, "incredibly"=> B_INCR, "intensely"=> B_INCR,
-          "major"=> B_INCR, "majorly"=> B_INCR, "more"=> B_INCR, "most"=> B_INCR, "particularly"=> B_INCR,
-          "purely"=> B_INCR, "quite"=> B_INCR, "really"=> B_INCR, "remarkably"=> B_INCR,
-          "so"=> B_INCR, "substantially"=> B_INCR,
-          "thoroughly"=> B_INCR, "total"=> B_INCR, "totally"=> B_INCR, "tremendous"=> B_INCR, "tremendously"=> B_INCR,
-          "uber"=> B_INCR, "unbelievably"=> B_INCR, "unusually"=> B_INCR, "utter"=> B_INCR, "utterly"=> B_INCR,
-          "very"=> B_INCR,
-          "almost"=> B_DECR, "barely"=> B_DECR, "hardly"=> B_DECR, "just enough"=> B_DECR,
-          "kind of"=> B_DECR, "kinda"=> B_DECR, "kindof"=> B_DECR, "kind-of"=> B_DECR,
-          "less"=> B_DECR, "little"=> B_DECR, "marginal"=> B_DECR, "marginally"=> B_DECR,
-          "occasional"=> B_DECR, "occasionally"=> B_DECR, "partly"=> B_DECR,
-          "scarce"=> B_DECR, "scarcely"=> B_DECR, "slight"=> B_DECR, "slightly"=> B_DECR, "somewhat"=> B_DECR,
-          "sort of"=> B_DECR, "sorta"=> B_DECR, "sortof"=> B_DECR, "sort-of"=> B_DECR
+    static ref BOOSTER_DICT: HashMap<UniCase<&'static str>, Booster> =  convert_args!(hashmap!(
+         "absolutely"=> Incr, "amazingly"=> Incr, "awfully"=> Incr,
+          "completely"=> Incr, "considerable"=> Incr, "considerably"=> Incr,
+          "decidedly"=> Incr, "deeply"=> Incr, "effing"=> Incr, "enormous"=> Incr, "enormously"=> Incr,
+          "entirely"=> Incr, "especially"=> Incr, "exceptional"=> Incr, "exceptionally"=> Incr,
+          "extreme"=> Incr, "extremely"=> Incr,
+          "fabulously"=> Incr, "flipping"=> Incr, "flippin"=> Incr, "frackin"=> Incr, "fracking"=> Incr,
+          "fricking"=> Incr, "frickin"=> Incr, "frigging"=> Incr, "friggin"=> Incr, "fully"=> Incr,
+          "fuckin"=> Incr, "fucking"=> Incr, "fuggin"=> Incr, "fugging"=> Incr,
+          "greatly"=> Incr, "hella"=> Incr, "highly"=> Incr, "hugely"=> Incr,
+          "incredible"=> Incr, "incredibly"=> Incr, "intensely"=> Incr,
+          "major"=> Incr, "majorly"=> Incr, "more"=> Incr, "most"=> Incr, "particularly"=> Incr,
+          "purely"=> Incr, "quite"=> Incr, "really"=> Incr, "remarkably"=> Incr,
+          "so"=> Incr, "substantially"=> Incr,
+          "thoroughly"=> Incr, "total"=> Incr, "totally"=> Incr, "tremendous"=> Incr, "tremendously"=> Incr,
+          "uber"=> Incr, "unbelievably"=> Incr, "unusually"=> Incr, "utter"=> Incr, "utterly"=> Incr,
+          "very"=> Incr,
+          "almost"=> Decr, "barely"=> Decr, "hardly"=> Decr, "just enough"=> Decr,
+          "kind of"=> Decr, "kinda"=> Decr, "kindof"=> Decr, "kind-of"=> Decr,
+          "less"=> Decr, "little"=> Decr, "marginal"=> Decr, "marginally"=> Decr,
+          "occasional"=> Decr, "occasionally"=> Decr, "partly"=> Decr,
+          "scarce"=> Decr, "scarcely"=> Decr, "slight"=> Decr, "slightly"=> Decr, "somewhat"=> Decr,
+          "sort of"=> Decr, "sorta"=> Decr, "sortof"=> Decr, "sort-of"=> Decr
 ));
 
-    /**
-     * These dicts were used in some WIP or planned features in the original
-     * I may implement them later if I can understand how they're intended to work
-     **/
-
-    // // check for sentiment laden idioms that do not contain lexicon words (future work, not yet implemented)
-    // static ref SENTIMENT_LADEN_IDIOMS: HashMap<&'static str, f64> = hashmap![
-    //      "cut the mustard" => 2.0, "hand to mouth" => -2.0,
-    //      "back handed" => -2.0, "blow smoke" => -2.0, "blowing smoke" => -2.0,
-    //      "upper hand" => 1.0, "break a leg" => 2.0,
-    //      "cooking with gas" => 2.0, "in the black" => 2.0, "in the red" => -2.0,
-    //      "on the ball" => 2.0, "under the weather" => -2.0];
-
-
-    // check for special case idioms containing lexicon words
-    static ref SPECIAL_CASE_IDIOMS: HashMap<UniCase<&'static str>, f64> = convert_args!(hashmap!(
-         "the shit" => 3.0, "the bomb" => 3.0, "bad ass" => 1.5, "badass" => 1.5, "yeah right" => -2.0,
-         "kiss of death" => -1.5, "to die for" => 3.0));
+    // Uppercase letters, with any accents written as combining marks, and non-word chars
+    static ref ALL_CAPS_RE: Regex = Regex::new(r"^[\p{Lu}\p{Lt}\p{M}\W]+$").unwrap();
 
-    static ref ALL_CAPS_RE: Regex = Regex::new(r"^[A-Z\W]+$").unwrap();
+    // Punctuation and the symbols that ASCII punctuation includes, like '$', '+' or '^'
+    static ref UNICODE_PUNCTUATION: Regex = Regex::new(r"^[\p{P}\p{Sc}\p{Sm}\p{Sk}]$").unwrap();
 
-    static ref PUNCTUATION: &'static str = "[!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~]";
+    pub static ref LEXICON: HashMap<UniCase<&'static str>, f64> = parse_raw_lexicon(RAW_LEXICON)
+                                                .expect("vader_lexicon.txt is well formed");
+    // The same lexicon with the standard deviation and ratings of every word
+    pub static ref DEFAULT_LEXICON: Lexicon = Lexicon::from_static_str(RAW_LEXICON, Repeats::Replace)
+                                                .expect("vader_lexicon.txt is well formed");
+    // The lexicon as vaderSentiment 3.3.2 reads it, used instead of DEFAULT_LEXICON in `Compat::Python` mode
+    static ref PYTHON_LEXICON: Lexicon = Lexicon::from_static_str(RAW_LEXICON, Repeats::Python)
+                                                .expect("vader_lexicon.txt is well formed");
+    pub static ref EMOJI_LEXICON: HashMap<&'static str, &'static str> = parse_raw_emoji_lexicon(RAW_EMOJI_LEXICON)
+                                                .expect("emoji_utf8_lexicon.txt is well formed");
 
-    pub static ref LEXICON: HashMap<UniCase<&'static str>, f64> = parse_raw_lexicon(RAW_LEXICON);
-    pub static ref EMOJI_LEXICON: HashMap<&'static str, &'static str> = parse_raw_emoji_lexicon(RAW_EMOJI_LEXICON);
+    static ref DEFAULT_IDIOMS: Arc<IdiomTable> = Arc::new(IdiomTable::default());
+    static ref DEFAULT_SPECIAL_IDIOMS: Arc<SpecialIdiomTable> = Arc::new(SpecialIdiomTable::default());
+    static ref DEFAULT_BOOSTERS: Arc<BoosterTable> = Arc::new(BoosterTable::default());
+    static ref DEFAULT_NEGATIONS: Arc<NegationTable> = Arc::new(NegationTable::default());
+    static ref DEFAULT_CONTRASTS: Arc<ContrastTable> = Arc::new(ContrastTable::default());
+    static ref DEFAULT_RULES: Arc<RulePipeline> = Arc::new(RulePipeline::default());
 
-    static ref STATIC_BUT: UniCase<&'static str> = UniCase::new("but");
     static ref STATIC_THIS: UniCase<&'static str> = UniCase::new("this");
     static ref STATIC_AT: UniCase<&'static str> = UniCase::new("at");
     static ref STATIC_LEAST: UniCase<&'static str> = UniCase::new("least");
@@ -115,42 +163,54 @@ lazy_static! {
     static ref STATIC_NEVER: UniCase<&'static str> = UniCase::new("never");
     static ref STATIC_KIND: UniCase<&'static str> = UniCase::new("kind");
     static ref STATIC_OF: UniCase<&'static str> = UniCase::new("of");
+    static ref STATIC_NO: UniCase<&'static str> = UniCase::new("no");
+    static ref STATIC_OR: UniCase<&'static str> = UniCase::new("or");
+    static ref STATIC_NOR: UniCase<&'static str> = UniCase::new("nor");
 
 
 }
 
 
 /**
- * Takes the raw text of the lexicon files and creates HashMaps
+ * Takes the raw text of the lexicon files and creates HashMaps. A malformed line is reported as a
+ * `LexiconError`; a repeated word replaces its earlier line.
  **/
-pub fn parse_raw_lexicon(raw_lexicon: &str) -> HashMap<UniCase<&str>, f64> {
+pub fn parse_raw_lexicon(raw_lexicon: &str) -> Result<HashMap<UniCase<&str>, f64>, LexiconError> {
     let lines = raw_lexicon.trim_end_matches("\n").split("\n");
-    let mut lex_dict = HashMap::new();
-    for line in lines {
+    let mut lex_dict: HashMap<UniCase<&str>, f64> = HashMap::new();
+    for (i, line) in lines.enumerate() {
         if line.is_empty() {
           continue;
         }
-        let mut split_line = line.split('\t');
-        let word = split_line.next().unwrap();
-        let val  = split_line.next().unwrap();
-        lex_dict.insert(UniCase::new(word), val.parse().unwrap());
+        let (word, val) = split_raw_line(line, i + 1)?;
+        let val = val.split('\t').next().unwrap_or(val);
+        let valence = val.trim().parse().map_err(|_|
+            LexiconError::new(i + 1, word.chars().count() + 2, LexiconErrorKind::InvalidValence(val.to_string())))?;
+        lex_dict.remove(&UniCase::new(word));
+        lex_dict.insert(UniCase::new(word), valence);
     }
-    lex_dict
+    Ok(lex_dict)
 }
 
-pub fn parse_raw_emoji_lexicon(raw_emoji_lexicon: &str) -> HashMap<&str, &str> {
+pub fn parse_raw_emoji_lexicon(raw_emoji_lexicon: &str) -> Result<HashMap<&str, &str>, LexiconError> {
     let lines = raw_emoji_lexicon.trim_end_matches("\n").split("\n");
     let mut emoji_dict = HashMap::new();
-    for line in lines {
+    for (i, line) in lines.enumerate() {
         if line.is_empty() {
           continue;
         }
-        let mut split_line = line.split('\t');
-        let word = split_line.next().unwrap();
-        let desc  = split_line.next().unwrap();
+        let (word, desc) = split_raw_line(line, i + 1)?;
         emoji_dict.insert(word, desc);
     }
-    emoji_dict
+    Ok(emoji_dict)
+}
+
+// Splits a raw lexicon line at its first tab
+fn split_raw_line(line: &str, line_no: usize) -> Result<(&str, &str), LexiconError> {
+    let mut split_line = line.splitn(2, '\t');
+    let word = split_line.next().unwrap_or(line);
+    split_line.next().map(|rest| (word, rest))
+        .ok_or_else(|| LexiconError::new(line_no, line.chars().count() + 1, LexiconErrorKind::MissingTab))
 }
 
 /**
@@ -158,40 +218,67 @@ pub fn parse_raw_emoji_lexicon(raw_emoji_lexicon: &str) -> HashMap<&str, &str> {
  **/
 struct ParsedText<'a> {
     tokens: Vec<UniCase<&'a str>>,
+    // The tokens as the tokenizer found them, with their spans
+    found: Vec<Token<'a>>,
     has_mixed_caps: bool,
     punc_amplifier: f64,
+    // Which tokens were elongated, empty if none were
+    elongated: Vec<bool>,
 }
 
 impl<'a> ParsedText<'a> {
     //Tokenizes and extracts useful properties of input text
-    fn from_text(text: &'a str) -> ParsedText {
-        let _tokens = ParsedText::tokenize(text);
-        let _has_mixed_caps = ParsedText::has_mixed_caps(&_tokens);
-        let _punc_amplifier = ParsedText::get_punctuation_emphasis(text);
+    #[cfg(test)]
+    fn from_text(text: &'a str, config: &VaderConfig) -> ParsedText<'a> {
+        let social = SocialMediaTokenizer { is_known: |_: &str| false };
+        let tokenizer: &dyn Tokenizer = if config.social_media {
+            &social
+        } else if config.compat == Compat::Python {
+            &PythonTokenizer
+        } else {
+            &DefaultTokenizer
+        };
+        ParsedText::from_tokenizer(text, config, tokenizer)
+    }
+
+    //Like from_text, but with the given tokenizer
+    fn from_tokenizer(text: &'a str, config: &VaderConfig, tokenizer: &dyn Tokenizer) -> ParsedText<'a> {
+        let mut found = Vec::new();
+        tokenizer.tokenize(text, &mut found);
+        let tokens: Vec<UniCase<&str>> = found.iter().map(|token| UniCase::new(token.text)).collect();
+        let _has_mixed_caps = match config.compat {
+            Compat::Legacy | Compat::Corrected => ParsedText::has_mixed_caps(&tokens),
+            Compat::Python => tokens.iter().any(|t| is_python_upper(t)) && !tokens.iter().all(|t| is_python_upper(t)),
+        };
+        let _tokens = tokens;
+        let _punc_amplifier = ParsedText::get_punctuation_emphasis(text, config);
         ParsedText {
             tokens: _tokens,
+            found,
             has_mixed_caps: _has_mixed_caps,
             punc_amplifier: _punc_amplifier,
+            elongated: Vec::new(),
          }
     }
 
-    fn tokenize(text: &str) -> Vec<UniCase<&str>> {
-        let tokens = text.split_whitespace()
-                                    .filter(|s| s.len() > 1)
-                                    .map(|s| ParsedText::strip_punc_if_word(s))
-                                    .map(UniCase::new)
-                                    .collect();
-        tokens
-    }
-
-    // Removes punctuation from words, ie "hello!!!" -> "hello" and ",don't??" -> "don't"
-    // Keeps most emoticons, ie ":^)" -> ":^)"\
-    fn strip_punc_if_word(token: &str) -> &str {
-        let stripped = token.trim_matches(|c| PUNCTUATION.contains(c));
-        if stripped.len() <= 1 {
-            return token;
+    //Marks the tokens that overlap the elongated words normalize_elongation replaced
+    fn mark_elongated(&mut self, replacements: &[Replacement]) {
+        if replacements.is_empty() {
+            return;
         }
-        stripped
+        self.elongated = self.found.iter()
+            .map(|token| replacements.iter().any(|r| r.rewritten.start < token.span.end && token.span.start < r.rewritten.end))
+            .collect();
+    }
+
+    fn is_elongated(&self, i: usize) -> bool {
+        self.elongated.get(i).cloned().unwrap_or(false)
+    }
+
+    fn tokenize(text: &str) -> Vec<UniCase<&str>> {
+        let mut found = Vec::new();
+        DefaultTokenizer.tokenize(text, &mut found);
+        found.into_iter().map(|token| UniCase::new(token.text)).collect()
     }
 
     // Determines if message has a mix of both all caps and non all caps words
@@ -211,36 +298,116 @@ impl<'a> ParsedText<'a> {
     }
 
     //uses empirical values to determine how the use of '?' and '!' contribute to sentiment
-    fn get_punctuation_emphasis(text: &str) -> f64 {
-       let emark_count: i32 = text.as_bytes().iter().filter(|b| **b == b'!').count() as i32;
-       let qmark_count: i32 = text.as_bytes().iter().filter(|b| **b == b'?').count() as i32;
-
-       let emark_emph = min(emark_count, MAX_EMARK) as f64 * EMARK_INCR;
-       let mut qmark_emph = (qmark_count as f64) * QMARK_INCR;
-       if qmark_count > MAX_QMARK {
-           qmark_emph = MAX_QMARK_INCR;
+    fn get_punctuation_emphasis(text: &str, config: &VaderConfig) -> f64 {
+       let (emark_count, qmark_count) = match config.compat {
+           Compat::Legacy | Compat::Corrected => text.chars().map(marks).fold((0, 0), |(e, q), (de, dq)| (e + de, q + dq)),
+           Compat::Python => (text.matches('!').count(), text.matches('?').count()),
+       };
+
+       let emark_emph = min(emark_count, config.max_emark) as f64 * config.emark_incr;
+       let mut qmark_emph = (qmark_count as f64) * config.qmark_incr;
+       if qmark_count > config.max_qmark {
+           qmark_emph = config.max_qmark_incr;
+       } else if qmark_count == 1 && config.compat == Compat::Python {
+           // The original treats a single '?' as just a question
+           qmark_emph = 0f64;
        }
        qmark_emph + emark_emph
     }
 }
 
+/**
+ * Buffers reused between the texts of a batch. The tokens borrow from the text they were found
+ * in, so their Vecs are still allocated for every text.
+ **/
+#[derive(Default)]
+struct Scratch {
+    text: String,
+    normalized: String,
+    elongations: Vec<Replacement>,
+    sentiments: Vec<f64>,
+}
+
+//Appends the description of the emoji at `original` in the text, separated from a word before it
+fn push_description(result: &mut String, replacements: &mut Option<&mut Vec<Replacement>>, prev_space: bool,
+                    description: &str, original: Range<usize>) {
+    let rewritten_start = result.len();
+    if !prev_space {
+        result.push(' ');
+    }
+    result.push_str(description);
+    if let Some(ref mut replacements) = *replacements {
+        replacements.push(Replacement { rewritten: rewritten_start..result.len(), original });
+    }
+}
+
+//Checks if a char only appears as part of a multi-codepoint emoji
+fn is_emoji_component(chr: char) -> bool {
+    // zero width joiner, variation selectors, keycap, skin tones and flag tags
+    matches!(chr, '\u{200D}' | '\u{FE0E}' | '\u{FE0F}' | '\u{20E3}' |
+                  '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0020}'..='\u{E007F}')
+}
+
 //Checks if all letters in token are capitalized
 fn is_all_caps<S: AsRef<str>>(token: S) -> bool {
     let token_ref = token.as_ref();
-    ALL_CAPS_RE.is_match(token_ref) && token_ref.len() > 1
+    token_ref.chars().nth(1).is_some() && ALL_CAPS_RE.is_match(token_ref)
 }
 
-//Checks if token is in the list of NEGATION_SCALAR
-fn is_negated(token: &UniCase<&str>) -> bool {
-    if NEGATION_TOKENS.contains(token) {
-        return true;
+//Checks for punctuation in any script, ie '¿', '…', '»' or '。', as well as ASCII punctuation
+fn is_punctuation(chr: char) -> bool {
+    if chr.is_ascii() {
+        return chr.is_ascii_punctuation();
+    }
+    marks(chr) != (0, 0) || UNICODE_PUNCTUATION.is_match(chr.encode_utf8(&mut [0; 4]))
+}
+
+//How many '!' and '?' a char stands for, counting fullwidth and doubled forms like '！' or '‼'
+fn marks(chr: char) -> (usize, usize) {
+    match chr {
+        '!' | '\u{FF01}' | '\u{FE57}' | '\u{2757}' | '\u{2755}' => (1, 0),
+        '?' | '\u{FF1F}' | '\u{FE56}' | '\u{2753}' | '\u{2754}' => (0, 1),
+        '\u{203C}' => (2, 0),
+        '\u{2047}' => (0, 2),
+        '\u{2048}' | '\u{2049}' => (1, 1),
+        _ => (0, 0),
+    }
+}
+
+//Checks if a token is in caps for emphasis, the way the configured implementation does
+fn is_emphasized(token: &str, config: &VaderConfig) -> bool {
+    match config.compat {
+        Compat::Legacy | Compat::Corrected => is_all_caps(token),
+        Compat::Python => is_python_upper(token),
+    }
+}
+
+//Python's str.isupper(), which is true for any token with cased letters that are all uppercase,
+//including one letter words like "I"
+fn is_python_upper(token: &str) -> bool {
+    token.chars().any(char::is_uppercase) && !token.chars().any(char::is_lowercase)
+}
+
+//Python's str.split() also splits on the ASCII file, group, record and unit separators
+fn is_python_whitespace(chr: char) -> bool {
+    chr.is_whitespace() || ('\u{1C}'..='\u{1F}').contains(&chr)
+}
+
+//Pushes the total valence further from zero to account for '!' and '?' emphasis
+fn apply_punctuation_emphasis(total: f64, punct_emph_amplifier: f64, config: &VaderConfig) -> f64 {
+    if total > 0f64 {
+        total + punct_emph_amplifier
+    } else if total == 0f64 && config.compat != Compat::Legacy {
+        // Only this crate's original scoring pushes a neutral total below zero
+        total
+    } else {
+        total - punct_emph_amplifier
     }
-    token.contains("n't")
 }
 
 //Normalizes score between -1.0 and 1.0. Alpha value is expected upper limit for a score
-fn normalize_score(score: f64) -> f64 {
-    let norm_score = score / (score * score + NORMALIZATION_ALPHA).sqrt();
+fn normalize_score(score: f64, alpha: f64) -> f64 {
+    let norm_score = score / (score * score + alpha).sqrt();
     if norm_score < -1.0 {
         return -1.0;
     } else if norm_score > 1.0 {
@@ -249,28 +416,9 @@ fn normalize_score(score: f64) -> f64 {
     norm_score
 }
 
-//Checks how previous tokens affect the valence of the current token
-fn scalar_inc_dec(token: &UniCase<&str>, valence: f64, has_mixed_caps: bool) -> f64 {
-    let mut scalar = 0.0;
-    if BOOSTER_DICT.contains_key(token) {
-        scalar = *BOOSTER_DICT.get(token).unwrap();
-        if valence < 0.0 {
-            scalar *= -1.0;
-        }
-        if is_all_caps(token) && has_mixed_caps {
-            if valence > 0.0 {
-                scalar += C_INCR;
-            } else {
-                scalar -= C_INCR;
-            }
-        }
-    }
-    scalar
-}
-
-fn sum_sentiment_scores(scores: Vec<f64>) -> (f64, f64, u32) {
+fn sum_sentiment_scores(scores: &[f64]) -> (f64, f64, u32) {
     let (mut pos_sum, mut neg_sum, mut neu_count) = (0f64, 0f64, 0);
-    for score in scores {
+    for &score in scores {
         if score > 0f64 {
             pos_sum += score + 1.0;
         } else if score < 0f64 {
@@ -282,37 +430,341 @@ fn sum_sentiment_scores(scores: Vec<f64>) -> (f64, f64, u32) {
     (pos_sum, neg_sum, neu_count)
 }
 
+//The lexicons an analyzer can score with
+#[derive(Clone)]
+enum LexiconSource<'a> {
+    Map(&'a HashMap<UniCase<&'a str>, f64>),
+    Lexicon(&'a Lexicon),
+    Shared(Arc<Lexicon>),
+}
+
+impl<'a> LexiconSource<'a> {
+    fn get(&self, word: &UniCase<&str>) -> Option<f64> {
+        match *self {
+            LexiconSource::Map(map) => map.get(word).cloned(),
+            LexiconSource::Lexicon(lexicon) => lexicon.get(word),
+            LexiconSource::Shared(ref lexicon) => lexicon.get(word),
+        }
+    }
+
+    fn contains_key(&self, word: &UniCase<&str>) -> bool {
+        match *self {
+            LexiconSource::Map(map) => map.contains_key(word),
+            LexiconSource::Lexicon(lexicon) => lexicon.contains(word),
+            LexiconSource::Shared(ref lexicon) => lexicon.contains(word),
+        }
+    }
+
+    fn std_dev(&self, word: &str) -> Option<f64> {
+        let entry = match *self {
+            LexiconSource::Map(_) => None,
+            LexiconSource::Lexicon(lexicon) => lexicon.entry(word),
+            LexiconSource::Shared(ref lexicon) => lexicon.entry(word),
+        };
+        entry.and_then(|entry| entry.std_dev)
+    }
+}
+
+/**
+ * Scores text with a lexicon and the VADER heuristics. Cloning an analyzer is cheap: lexicons
+ * are either borrowed or shared through an `Arc`.
+ **/
+#[derive(Clone)]
 pub struct SentimentIntensityAnalyzer<'a> {
-    lexicon: &'a HashMap<UniCase<&'a str>, f64>,
+    lexicon: LexiconSource<'a>,
     emoji_lexicon: &'a HashMap<&'a str, &'a str>,
+    idioms: Arc<IdiomTable>,
+    special_idioms: Arc<SpecialIdiomTable>,
+    boosters: Arc<BoosterTable>,
+    negations: Arc<NegationTable>,
+    contrasts: Arc<ContrastTable>,
+    rules: Arc<RulePipeline>,
+    // The language the word tables are for
+    language: Language,
+    // Replaces the built in tokenizers when set
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    config: VaderConfig,
 }
 
-impl<'a> SentimentIntensityAnalyzer<'a> {
-    pub fn new() -> SentimentIntensityAnalyzer<'static>{
+/**
+ * An analyzer with no borrowed data, as returned by `new` and `from_shared_lexicon`.
+ * It is `Send + Sync + Clone`.
+ **/
+pub type OwnedSentimentIntensityAnalyzer = SentimentIntensityAnalyzer<'static>;
+
+/**
+ * Builds a `SentimentIntensityAnalyzer` with a custom lexicon and/or scoring constants.
+ * Anything that isn't set falls back to the same defaults as `SentimentIntensityAnalyzer::new`.
+ **/
+pub struct SentimentIntensityAnalyzerBuilder<'a> {
+    lexicon: LexiconSource<'a>,
+    idioms: Arc<IdiomTable>,
+    special_idioms: Arc<SpecialIdiomTable>,
+    boosters: Arc<BoosterTable>,
+    negations: Arc<NegationTable>,
+    contrasts: Arc<ContrastTable>,
+    rules: Arc<RulePipeline>,
+    // The language the word tables are for
+    language: Language,
+    // Replaces the built in tokenizers when set
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    config: VaderConfig,
+}
+
+impl<'a> SentimentIntensityAnalyzerBuilder<'a> {
+    pub fn lexicon(mut self, lexicon: &'a Lexicon) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.lexicon = LexiconSource::Lexicon(lexicon);
+        self
+    }
+
+    //Uses a lexicon that is shared with other analyzers and threads instead of borrowed
+    pub fn shared_lexicon(mut self, lexicon: Arc<Lexicon>) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.lexicon = LexiconSource::Shared(lexicon);
+        self
+    }
+
+    //Uses a lexicon in the format returned by `parse_raw_lexicon`
+    pub fn lexicon_map(mut self, lexicon: &'a HashMap<UniCase<&'a str>, f64>) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.lexicon = LexiconSource::Map(lexicon);
+        self
+    }
+
+    pub fn config(mut self, config: VaderConfig) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.config = config;
+        self
+    }
+
+    //Replaces the sentiment laden idioms, e.g. with `IdiomTable::default()` plus domain phrases
+    pub fn idioms(mut self, idioms: IdiomTable) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.idioms = Arc::new(idioms);
+        self
+    }
+
+    pub fn special_idioms(mut self, special_idioms: SpecialIdiomTable) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.special_idioms = Arc::new(special_idioms);
+        self
+    }
+
+    pub fn boosters(mut self, boosters: BoosterTable) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.boosters = Arc::new(boosters);
+        self
+    }
+
+    pub fn negations(mut self, negations: NegationTable) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.negations = Arc::new(negations);
+        self
+    }
+
+    /**
+     * Scores with the lexicon, word tables and rules of a language, replacing any set before.
+     * Scoring constants and the tokenizer are kept.
+     **/
+    pub fn language_pack(mut self, pack: &LanguagePack) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.lexicon = LexiconSource::Lexicon(pack.lexicon());
+        self.idioms = pack.idioms.clone();
+        self.special_idioms = pack.special_idioms.clone();
+        self.boosters = pack.boosters.clone();
+        self.negations = pack.negations.clone();
+        self.contrasts = pack.contrasts.clone();
+        self.rules = pack.rules.clone();
+        self.language = pack.language();
+        self
+    }
+
+    //Replaces the conjunctions that shift emphasis to the words after them, like "but"
+    pub fn contrasts(mut self, contrasts: ContrastTable) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.contrasts = Arc::new(contrasts);
+        self
+    }
+
+    //Replaces the rules that adjust valences, e.g. `RulePipeline::default()` with a rule removed
+    pub fn rules(mut self, rules: RulePipeline) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.rules = Arc::new(rules);
+        self
+    }
+
+    /**
+     * Tokenizes text with `tokenizer` instead of the built in tokenizers, including the ones
+     * `VaderConfig::compat` and `VaderConfig::social_media` choose.
+     **/
+    pub fn tokenizer<T: Tokenizer + 'static>(mut self, tokenizer: T) -> SentimentIntensityAnalyzerBuilder<'a> {
+        self.tokenizer = Some(Arc::new(tokenizer));
+        self
+    }
+
+    pub fn build(self) -> SentimentIntensityAnalyzer<'a> {
+        let lexicon = match self.lexicon {
+            LexiconSource::Lexicon(lexicon) if self.config.compat == Compat::Python &&
+                                               ptr::eq(lexicon, &*DEFAULT_LEXICON) => LexiconSource::Lexicon(&PYTHON_LEXICON),
+            lexicon => lexicon,
+        };
         SentimentIntensityAnalyzer {
-            lexicon: &LEXICON,
+            lexicon,
             emoji_lexicon: &EMOJI_LEXICON,
+            idioms: self.idioms,
+            special_idioms: self.special_idioms,
+            boosters: self.boosters,
+            negations: self.negations,
+            contrasts: self.contrasts,
+            language: self.language,
+            rules: self.rules,
+            tokenizer: self.tokenizer,
+            config: self.config,
+        }
+    }
+}
+
+impl<'a> SentimentIntensityAnalyzer<'a> {
+    //A builder holding this analyzer's lexicon, tables, rules, tokenizer and config
+    fn into_builder(self) -> SentimentIntensityAnalyzerBuilder<'a> {
+        SentimentIntensityAnalyzerBuilder {
+            lexicon: self.lexicon,
+            idioms: self.idioms,
+            special_idioms: self.special_idioms,
+            boosters: self.boosters,
+            negations: self.negations,
+            contrasts: self.contrasts,
+            rules: self.rules,
+            language: self.language,
+            tokenizer: self.tokenizer,
+            config: self.config,
         }
     }
 
+    pub fn new() -> SentimentIntensityAnalyzer<'static>{
+        SentimentIntensityAnalyzer::builder().build()
+    }
+
     pub fn from_lexicon<'b>(_lexicon: &'b HashMap<UniCase<&str>, f64>) ->
                                         SentimentIntensityAnalyzer<'b> {
-        SentimentIntensityAnalyzer {
-            lexicon: _lexicon,
-            emoji_lexicon: &EMOJI_LEXICON,
+        SentimentIntensityAnalyzer::builder().lexicon_map(_lexicon).build()
+    }
+
+    pub fn from_custom_lexicon(lexicon: &'a Lexicon) -> SentimentIntensityAnalyzer<'a> {
+        SentimentIntensityAnalyzer::builder().lexicon(lexicon).build()
+    }
+
+    /**
+     * Creates an analyzer that doesn't borrow anything, so it can be stored in long-lived
+     * structs and sent to or shared between threads.
+     **/
+    pub fn from_shared_lexicon(lexicon: Arc<Lexicon>) -> OwnedSentimentIntensityAnalyzer {
+        SentimentIntensityAnalyzer::builder().shared_lexicon(lexicon).build()
+    }
+
+    /**
+     * An analyzer for `language`, or None if its pack isn't compiled in. Enable the `spanish`,
+     * `portuguese`, `german` or `french` cargo features for the languages you need.
+     **/
+    pub fn for_language(language: Language) -> Option<OwnedSentimentIntensityAnalyzer> {
+        language.pack().map(|pack| SentimentIntensityAnalyzer::builder().language_pack(pack).build())
+    }
+
+    pub fn builder() -> SentimentIntensityAnalyzerBuilder<'a> {
+        SentimentIntensityAnalyzerBuilder {
+            lexicon: LexiconSource::Lexicon(&DEFAULT_LEXICON),
+            idioms: DEFAULT_IDIOMS.clone(),
+            special_idioms: DEFAULT_SPECIAL_IDIOMS.clone(),
+            boosters: DEFAULT_BOOSTERS.clone(),
+            negations: DEFAULT_NEGATIONS.clone(),
+            contrasts: DEFAULT_CONTRASTS.clone(),
+            language: Language::English,
+            rules: DEFAULT_RULES.clone(),
+            tokenizer: None,
+            config: VaderConfig::default(),
         }
     }
 
-    fn get_total_sentiment(&self, sentiments: Vec<f64>, punct_emph_amplifier: f64) -> HashMap<&str, f64> {
+    pub fn config(&self) -> &VaderConfig {
+        &self.config
+    }
+
+    //The language of the analyzer's word tables, English unless set with `language_pack`
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    //The lexicon in use, or None for one given as a map to `lexicon_map` or `from_lexicon`
+    pub fn lexicon(&self) -> Option<&Lexicon> {
+        match self.lexicon {
+            LexiconSource::Map(_) => None,
+            LexiconSource::Lexicon(lexicon) => Some(lexicon),
+            LexiconSource::Shared(ref lexicon) => Some(lexicon),
+        }
+    }
+
+    pub fn idioms(&self) -> &IdiomTable {
+        &self.idioms
+    }
+
+    pub fn special_idioms(&self) -> &SpecialIdiomTable {
+        &self.special_idioms
+    }
+
+    pub fn boosters(&self) -> &BoosterTable {
+        &self.boosters
+    }
+
+    pub fn negations(&self) -> &NegationTable {
+        &self.negations
+    }
+
+    pub fn contrasts(&self) -> &ContrastTable {
+        &self.contrasts
+    }
+
+    pub fn rules(&self) -> &RulePipeline {
+        &self.rules
+    }
+
+    /**
+     * Mutable access to the analyzer's word tables. The tables are shared between clones of
+     * an analyzer, so the first edit copies the table and leaves other analyzers unchanged.
+     **/
+    pub fn idioms_mut(&mut self) -> &mut IdiomTable {
+        Arc::make_mut(&mut self.idioms)
+    }
+
+    pub fn special_idioms_mut(&mut self) -> &mut SpecialIdiomTable {
+        Arc::make_mut(&mut self.special_idioms)
+    }
+
+    pub fn boosters_mut(&mut self) -> &mut BoosterTable {
+        Arc::make_mut(&mut self.boosters)
+    }
+
+    pub fn negations_mut(&mut self) -> &mut NegationTable {
+        Arc::make_mut(&mut self.negations)
+    }
+
+    pub fn contrasts_mut(&mut self) -> &mut ContrastTable {
+        Arc::make_mut(&mut self.contrasts)
+    }
+
+    pub fn rules_mut(&mut self) -> &mut RulePipeline {
+        Arc::make_mut(&mut self.rules)
+    }
+
+    //Tokenizes text with the tokenizer set on the builder, or the built in one the config picks
+    fn parse<'t>(&self, text: &'t str) -> ParsedText<'t> {
+        let social;
+        let tokenizer: &dyn Tokenizer = match self.tokenizer {
+            Some(ref tokenizer) => &**tokenizer,
+            None if self.config.social_media => {
+                social = SocialMediaTokenizer { is_known: |word: &str| self.is_known(word) };
+                &social
+            },
+            None if self.config.compat == Compat::Python => &PythonTokenizer,
+            None => &DefaultTokenizer,
+        };
+        ParsedText::from_tokenizer(text, &self.config, tokenizer)
+    }
+
+    fn get_total_sentiment(&self, sentiments: &[f64], punct_emph_amplifier: f64) -> SentimentScores {
         let (mut neg, mut neu, mut pos, mut compound) = (0f64, 0f64, 0f64, 0f64);
-        if sentiments.len() > 0 {
-            let mut total_sentiment: f64 = sentiments.iter().sum();
-            if total_sentiment > 0f64 {
-                total_sentiment += punct_emph_amplifier;
-            } else {
-                total_sentiment -= punct_emph_amplifier;
-            }
-            compound = normalize_score(total_sentiment);
+        if !sentiments.is_empty() {
+            let total_sentiment = apply_punctuation_emphasis(sentiments.iter().sum(), punct_emph_amplifier, &self.config);
+            compound = normalize_score(total_sentiment, self.config.normalization_alpha);
 
             let (mut pos_sum, mut neg_sum, neu_count) = sum_sentiment_scores(sentiments);
 
@@ -327,194 +779,380 @@ impl<'a> SentimentIntensityAnalyzer<'a> {
             neg = (neg_sum / total).abs();
             neu = (neu_count as f64 / total).abs();
         }
-        let sentiment_dict = hashmap!["neg" => neg,
-                                      "neu" => neu,
-                                      "pos" => pos,
-                                      "compound" => compound];
-        sentiment_dict
+        SentimentScores { neg, neu, pos, compound }
     }
 
-    pub fn polarity_scores(&self, text: &str) -> HashMap<&str, f64>{
-        let text = self.append_emoji_descriptions(text);
-        let parsedtext = ParsedText::from_text(&text);
-        let mut sentiments = Vec::new();
-        let tokens = &parsedtext.tokens;
+    pub fn polarity_scores(&self, text: &str) -> SentimentScores {
+        self.scores_and_token_count(text).0
+    }
 
-        for (i, word) in tokens.iter().enumerate() {
-            if BOOSTER_DICT.contains_key(word) {
-                sentiments.push(0f64);
-            } else if i < tokens.len() - 1 && word == &*STATIC_KIND
-                                  && tokens[i + 1] == *STATIC_OF {
-                sentiments.push(0f64);
-            } else {
-                sentiments.push(self.sentiment_valence(&parsedtext, word, i));
+    /**
+     * Detects the language of `text` and scores it with the matching language pack, keeping
+     * this analyzer's scoring constants and tokenizer. Text in the analyzer's own language is
+     * scored with its own tables, and so is text that is too short or too ambiguous to detect,
+     * like "nice" or "omg no". Languages without a pack, or whose cargo feature isn't enabled,
+     * are an `UnsupportedLanguageError` rather than a neutral score.
+     **/
+    pub fn polarity_scores_auto(&self, text: &str) -> Result<LanguageScores, UnsupportedLanguageError> {
+        let guess = detect_language(text);
+        let language = match guess {
+            Some(guess) if detect::is_routable(&guess, text) => match guess.language() {
+                Some(language) if language == self.language || language.is_available() => language,
+                _ => return Err(UnsupportedLanguageError { guess }),
+            },
+            _ => self.language,
+        };
+        let scores = match language.pack() {
+            Some(pack) if language != self.language => {
+                self.clone().into_builder().language_pack(pack).build().polarity_scores(text)
+            },
+            _ => self.polarity_scores(text),
+        };
+        Ok(LanguageScores { language, scores, guess })
+    }
+
+    fn scores_and_token_count(&self, text: &str) -> (SentimentScores, usize) {
+        self.scores_reusing(text, &mut Scratch::default())
+    }
+
+    fn scores_reusing(&self, text: &str, scratch: &mut Scratch) -> (SentimentScores, usize) {
+        let mut described = mem::take(&mut scratch.text);
+        described.clear();
+        self.describe_emoji_into(text, None, &mut described);
+        let mut normalized = mem::take(&mut scratch.normalized);
+        let mut elongations = mem::take(&mut scratch.elongations);
+        let text = self.normalize_elongation(&described, &mut elongations, &mut normalized);
+        let mut parsedtext = self.parse(text);
+        parsedtext.mark_elongated(&elongations);
+        let sentiments = &mut scratch.sentiments;
+        let context = RuleContext::new(self, &parsedtext);
+        self.token_sentiments_into(&context, None, sentiments);
+        let mut valences = Valences::new(sentiments, None);
+        self.rules.adjust_text(&context, &mut valences);
+        let (_, punc_amplifier) = valences.into_parts();
+        let scores = self.get_total_sentiment(sentiments, punc_amplifier);
+        let token_count = parsedtext.tokens.len();
+        scratch.text = described;
+        scratch.normalized = normalized;
+        scratch.elongations = elongations;
+        (scores, token_count)
+    }
+
+    /**
+     * Scores many texts, reusing the same buffers for all of them instead of allocating new ones
+     * for every text. The results are identical to calling `polarity_scores` on each text.
+     **/
+    pub fn polarity_scores_batch<S: AsRef<str>>(&self, texts: &[S]) -> Vec<SentimentScores> {
+        let mut scratch = Scratch::default();
+        texts.iter().map(|text| self.scores_reusing(text.as_ref(), &mut scratch).0).collect()
+    }
+
+    /**
+     * Scores a slice of texts on the rayon thread pool. Results are in the order of the texts and
+     * identical to calling `polarity_scores` on each of them.
+     **/
+    #[cfg(feature = "rayon")]
+    pub fn polarity_scores_par_batch<S: AsRef<str> + Sync>(&self, texts: &[S]) -> Vec<SentimentScores> {
+        self.polarity_scores_par_iter(texts).collect()
+    }
+
+    /**
+     * Scores the texts of a parallel iterator, reusing buffers within each rayon job.
+     * Collecting the result keeps the order of the input.
+     **/
+    #[cfg(feature = "rayon")]
+    pub fn polarity_scores_par_iter<'s, I>(&'s self, texts: I) -> impl ParallelIterator<Item = SentimentScores> + 's
+        where I: IntoParallelIterator, I::Item: AsRef<str>, I::Iter: 's {
+        texts.into_par_iter().map_init(Scratch::default, move |scratch, text| self.scores_reusing(text.as_ref(), scratch).0)
+    }
+
+    /**
+     * Splits text into sentences with `split_sentences` and scores each of them on its own, so
+     * a "but" or a "!!!" only affects its own sentence. Returns the sentence scores along with
+     * the mean, length-weighted, highest and lowest compound scores.
+     **/
+    pub fn document_scores(&self, text: &str) -> DocumentScores {
+        let sentences = split_sentences(text).into_iter()
+            .map(|sentence| {
+                let (scores, tokens) = self.scores_and_token_count(sentence.text);
+                SentenceScores {
+                    text: sentence.text.to_string(),
+                    start: sentence.start,
+                    end: sentence.end,
+                    tokens,
+                    scores,
+                }
+            })
+            .collect();
+        DocumentScores::new(sentences)
+    }
+
+    /**
+     * Scores text like `polarity_scores`, but also reports how every token was scored: its
+     * lexicon valence, each rule that changed it, the punctuation amplifier and the
+     * normalization of the total into the compound score.
+     **/
+    pub fn polarity_scores_explained(&self, text: &str) -> Explanation {
+        let mut replacements = Vec::new();
+        let described = self.describe_emoji(text, Some(&mut replacements));
+        let (mut normalized, mut elongations) = (String::new(), Vec::new());
+        let scored = self.normalize_elongation(&described, &mut elongations, &mut normalized);
+        let mut parsedtext = self.parse(scored);
+        parsedtext.mark_elongated(&elongations);
+        let context = RuleContext::new(self, &parsedtext);
+        let mut traces = Vec::with_capacity(parsedtext.tokens.len());
+        let mut sentiments = Vec::with_capacity(parsedtext.tokens.len());
+        self.token_sentiments_into(&context, Some(&mut traces), &mut sentiments);
+        let mut offsets = OffsetMap::new(text, replacements);
+        let elongations = OffsetMap::new(&described, elongations);
+        for (trace, token) in traces.iter_mut().zip(&parsedtext.found) {
+            trace.span = offsets.span_of(elongations.original_range(token.span.clone()));
+            trace.is_emoticon = token.is_emoticon;
+        }
+        let mut valences = Valences::new(&mut sentiments, Some(&mut traces));
+        self.rules.adjust_text(&context, &mut valences);
+        let (idioms, punc_amplifier) = valences.into_parts();
+        for (trace, &sentiment) in traces.iter_mut().zip(&sentiments) {
+            trace.valence = sentiment;
+        }
+
+        // Folded from 0.0, since `Sum` starts at -0.0 and an empty text would total -0
+        let raw_total = sentiments.iter().fold(0.0, |total, sentiment| total + sentiment);
+        let amplified_total = if sentiments.is_empty() {
+            raw_total
+        } else {
+            apply_punctuation_emphasis(raw_total, punc_amplifier, &self.config)
+        };
+        let scores = self.get_total_sentiment(&sentiments, punc_amplifier);
+        Explanation {
+            tokens: traces,
+            idioms,
+            punctuation_amplifier: punc_amplifier,
+            normalization: Normalization {
+                raw_total,
+                amplified_total,
+                alpha: self.config.normalization_alpha,
+                compound: scores.compound,
+            },
+            scores,
+        }
+    }
+
+    /**
+     * Scores text like `polarity_scores`, and estimates how far the compound score could move
+     * given the standard deviations of the words' ratings. The uncertainty is `None` when the
+     * lexicon has no standard deviations, as with lexicons from `parse_raw_lexicon`.
+     **/
+    pub fn polarity_scores_with_uncertainty(&self, text: &str) -> (SentimentScores, Option<Uncertainty>) {
+        let explanation = self.polarity_scores_explained(text);
+        if let LexiconSource::Map(_) = self.lexicon {
+            return (explanation.scores, None);
+        }
+        let mut variance = 0f64;
+        let mut unrated_words = 0;
+        for trace in &explanation.tokens {
+            if trace.base_valence.is_none() {
+                continue;
+            }
+            // How much the token's final valence changes per unit of its lexicon valence.
+            // Boosters and caps emphasis only shift it, idioms and modifiers replace it.
+            let mut slope = 1f64;
+            for adjustment in &trace.adjustments {
+                match adjustment.rule {
+                    Rule::Modifier | Rule::SpecialIdiom | Rule::LadenIdiom => slope = 0f64,
+                    Rule::CapsEmphasis | Rule::Elongation | Rule::Booster => (),
+                    _ => if adjustment.before != 0f64 {
+                        slope *= adjustment.after / adjustment.before;
+                    },
+                }
+            }
+            match self.lexicon.std_dev(&trace.token) {
+                Some(std_dev) => variance += (slope * std_dev).powi(2),
+                None => if slope != 0f64 {
+                    unrated_words += 1;
+                },
             }
         }
-        but_check(tokens, &mut sentiments);
-        self.get_total_sentiment(sentiments, parsedtext.punc_amplifier)
+        let std_dev = variance.sqrt();
+        let total = explanation.normalization.amplified_total;
+        let alpha = self.config.normalization_alpha;
+        let uncertainty = Uncertainty {
+            std_dev,
+            compound_low: normalize_score(total - 1.96 * std_dev, alpha),
+            compound_high: normalize_score(total + 1.96 * std_dev, alpha),
+            unrated_words,
+        };
+        (explanation.scores, Some(uncertainty))
     }
 
-    //Removes emoji and appends their description to the end the input text
-    fn append_emoji_descriptions(&self, text: &str) -> String {
-        let mut result = String::new();
-        let mut prev_space = true;
-        for chr in text.chars() {
-            let chr_string = chr.to_string();
-            if let Some(chr_replacement) = self.emoji_lexicon.get(chr_string.as_str()) {
-                if !prev_space {
-                    result.push(' ');
+    //Scores each token on its own, before the "but" contrast and normalization are applied
+    fn token_sentiments_into(&self, context: &RuleContext, mut traces: Option<&mut Vec<TokenTrace>>,
+                             sentiments: &mut Vec<f64>) {
+        let tokens = context.tokens();
+        sentiments.clear();
+        for (i, word) in tokens.iter().enumerate() {
+            let mut trace = traces.as_ref().map(|_| TokenTrace::new(word, self.lexicon.get(word)));
+            let sentiment = if self.boosters.contains(word) || (i < tokens.len() - 1 && word == &*STATIC_KIND
+                                                                   && tokens[i + 1] == *STATIC_OF) {
+                if let Some(ref mut trace) = trace {
+                    let base = trace.base_valence.unwrap_or(0f64);
+                    record(&mut Some(&mut trace.adjustments), Rule::Modifier, None, base, 0f64);
                 }
-                result.push_str(chr_replacement);
-                prev_space = false;
+                0f64
             } else {
-                prev_space = chr == ' ';
-                result.push(chr);
+                self.sentiment_valence(context, word, i, trace.as_mut().map(|t| &mut t.adjustments))
+            };
+            sentiments.push(sentiment);
+            if let (Some(traces), Some(mut trace)) = (traces.as_mut(), trace) {
+                trace.valence = sentiment;
+                traces.push(trace);
             }
         }
+    }
+
+    //Checks if a word means something to the analyzer, as a lexicon word, booster or negation
+    fn is_known(&self, word: &str) -> bool {
+        self.lexicon.contains_key(&UniCase::new(word)) || self.boosters.contains(word) || self.negations.is_negation(word)
+    }
+
+    //Rewrites elongated words into normalized when normalize_elongation is on, and returns the
+    //text to tokenize
+    fn normalize_elongation<'t>(&self, text: &'t str, replacements: &mut Vec<Replacement>,
+                                normalized: &'t mut String) -> &'t str {
+        replacements.clear();
+        normalized.clear();
+        if !self.config.normalize_elongation {
+            return text;
+        }
+        elongation::normalize_into(text, |word| self.is_known(word), replacements, normalized);
+        normalized
+    }
+
+    //Replaces emoji with their descriptions, matching the longest emoji in each grapheme cluster
+    #[cfg(test)]
+    fn append_emoji_descriptions(&self, text: &str) -> String {
+        self.describe_emoji(text, None)
+    }
+
+    //Replaces emoji like append_emoji_descriptions, recording where each replacement went
+    fn describe_emoji(&self, text: &str, replacements: Option<&mut Vec<Replacement>>) -> String {
+        let mut result = String::new();
+        self.describe_emoji_into(text, replacements, &mut result);
         result
     }
 
-    fn sentiment_valence(&self, parsed: &ParsedText, word: &UniCase<&str>, i: usize) -> f64 {
-        let mut valence = 0f64;
-        let tokens = &parsed.tokens;
-        if let Some(word_valence) = self.lexicon.get(word) {
-            valence = *word_valence;
-            if is_all_caps(word) && parsed.has_mixed_caps {
-                if valence > 0f64 {
-                    valence += C_INCR;
-                } else {
-                    valence -= C_INCR
+    fn describe_emoji_into(&self, text: &str, mut replacements: Option<&mut Vec<Replacement>>, result: &mut String) {
+        if text.is_ascii() {
+            // Every emoji in the lexicon has a non-ASCII char
+            result.push_str(text);
+            return;
+        }
+        let mut prev_space = true;
+        if self.config.compat == Compat::Python {
+            // The original looks up one code point at a time, so it only knows single code point
+            // emoji and describes the parts of longer ones separately
+            for (i, chr) in text.char_indices() {
+                let original = i..i + chr.len_utf8();
+                match self.emoji_lexicon.get(&text[original.clone()]) {
+                    Some(replacement) => {
+                        push_description(result, &mut replacements, prev_space, replacement, original);
+                        prev_space = false;
+                    },
+                    None => {
+                        prev_space = chr == ' ';
+                        result.push(chr);
+                    },
                 }
             }
-            for start_i in 0..3 {
-                if i > start_i && !self.lexicon.contains_key(
-                                &tokens[i - start_i - 1]) {
-                    let mut s = scalar_inc_dec(&tokens[i - start_i - 1], valence, parsed.has_mixed_caps);
-                    if start_i == 1 {
-                        s *= 0.95;
-                    } else if start_i == 2 {
-                        s *= 0.9
+            return;
+        }
+        for grapheme in text.graphemes(true) {
+            let mut rest = grapheme;
+            let mut matched = false;
+            while let Some(chr) = rest.chars().next() {
+                // Skin tones, joiners and variation selectors only modify the emoji before them
+                if matched && is_emoji_component(chr) {
+                    let original_start = rest.as_ptr() as usize - text.as_ptr() as usize;
+                    let original = original_start..original_start + chr.len_utf8();
+                    rest = &rest[chr.len_utf8()..];
+                    if let Some(ref mut replacements) = replacements {
+                        // Dropped with the emoji right before it, or on its own after other chars
+                        match replacements.last_mut() {
+                            Some(last) if last.original.end == original.start => last.original.end = original.end,
+                            _ => replacements.push(Replacement { rewritten: result.len()..result.len(), original }),
+                        }
                     }
-                    valence += s;
-                    valence = negation_check(valence, tokens, start_i, i);
-                    if start_i == 2 {
-                        valence = special_idioms_check(valence, tokens, i);
+                    continue;
+                }
+                if !chr.is_ascii() && marks(chr) != (0, 0) {
+                    // Emoji like '‼' or '❗' emphasize the text like "!!" or '!' rather than
+                    // being described, without the variation selectors that may follow them
+                    let original_start = rest.as_ptr() as usize - text.as_ptr() as usize;
+                    let rewritten_start = result.len();
+                    result.push(chr);
+                    if let Some(ref mut replacements) = replacements {
+                        replacements.push(Replacement { rewritten: rewritten_start..result.len(),
+                                                        original: original_start..original_start + chr.len_utf8() });
                     }
+                    prev_space = false;
+                    matched = true;
+                    rest = &rest[chr.len_utf8()..];
+                } else if let Some((len, replacement)) = self.longest_emoji(rest) {
+                    let original_start = rest.as_ptr() as usize - text.as_ptr() as usize;
+                    push_description(result, &mut replacements, prev_space, replacement,
+                                     original_start..original_start + len);
+                    prev_space = false;
+                    matched = true;
+                    rest = &rest[len..];
+                } else {
+                    prev_space = chr == ' ';
+                    result.push(chr);
+                    rest = &rest[chr.len_utf8()..];
                 }
             }
-            valence = least_check(valence, tokens, i);
         }
-        valence
     }
-}
 
-/**
- * Check for specific patterns or tokens, and modify sentiment as needed
- **/
-fn negation_check(valence: f64, tokens: &[UniCase<&str>], start_i: usize, i: usize) -> f64 {
-   let mut valence = valence;
-   if start_i == 0 {
-       if is_negated(&tokens[i - start_i - 1]) {
-           valence *= NEGATION_SCALAR;
-       }
-   } else if start_i == 1 {
-       if tokens[i - 2] == *STATIC_NEVER &&
-         (tokens[i - 1] == *STATIC_SO ||
-          tokens[i - 1] == *STATIC_THIS) {
-           valence *= 1.25
-       } else if tokens[i - 2] == *STATIC_WITHOUT && tokens[i - 1] == *STATIC_DOUBT {
-           valence *= 1.0
-       } else if is_negated(&tokens[i - start_i - 1]) {
-           valence *= NEGATION_SCALAR;
-       }
-   } else if start_i == 2 {
-       if tokens[i - 3] == *STATIC_NEVER &&
-          tokens[i - 2] == *STATIC_SO || tokens[i - 2] == *STATIC_THIS||
-          tokens[i - 1] == *STATIC_SO || tokens[i - 1] == *STATIC_THIS {
-           valence *= 1.25
-       } else if tokens[i - 3] == *STATIC_WITHOUT &&
-                 tokens[i - 2] == *STATIC_DOUBT ||
-                 tokens[i - 1] == *STATIC_DOUBT {
-           valence *= 1.0;
-       } else if is_negated(&tokens[i - start_i - 1]) {
-           valence *= NEGATION_SCALAR;
-       }
-   }
-   valence
-}
-
-// If "but" is in the tokens, scales down the sentiment of words before "but" and
-// adds more emphasis to the words after
-fn but_check(tokens: &[UniCase<&str>], sentiments: &mut Vec<f64>) {
-    match tokens.iter().position(|&s| s == *STATIC_BUT) {
-        Some(but_index) => {
-            for i in 0..sentiments.len() {
-                if i < but_index {
-                    sentiments[i] *= 0.5;
-                } else if i > but_index {
-                    sentiments[i] *= 1.5;
-                }
-            }
-        },
-        None => return,
+    //Finds the longest prefix of text that is in the emoji lexicon, and its byte length
+    fn longest_emoji(&self, text: &str) -> Option<(usize, &'a str)> {
+        text.char_indices().rev()
+            .map(|(i, chr)| i + chr.len_utf8())
+            .filter_map(|end| self.emoji_lexicon.get(&text[..end]).map(|replacement| (end, *replacement)))
+            .next()
     }
-}
 
-fn least_check(_valence: f64, tokens: &[UniCase<&str>], i: usize) -> f64 {
-    let mut valence = _valence;
-    if i > 1 && tokens[i - 1] == *STATIC_LEAST
-             && tokens[i - 2] == *STATIC_AT
-             && tokens[i - 2] == *STATIC_VERY {
-        valence *= NEGATION_SCALAR;
-    } else if i > 0 && tokens[i - 1] == *STATIC_LEAST {
-        valence *= NEGATION_SCALAR;
+    //Runs the word rules on a lexicon word, other tokens have no valence of their own
+    fn sentiment_valence(&self, context: &RuleContext, word: &UniCase<&str>, i: usize, log: Log) -> f64 {
+        match self.lexicon.get(word) {
+            Some(valence) => {
+                let mut word = Word::new(i, valence, log);
+                self.rules.adjust_word(context, &mut word);
+                word.valence()
+            },
+            None => 0f64,
+        }
     }
-    valence
 }
 
-// //This was present in the original python implementation, but unused
-// fn idioms_check(valence: f64, text: &str) -> f64 {
-//     let mut total_valence = 0f64;
-//     let mut count = 0;
-//     for (idiom, val) in SENTIMENT_LADEN_IDIOMS.iter() {
-//         if text.contains(idiom) {
-//             total_valence += val;
-//             count += 1;
-//         }
-//     }
-//     if count > 0 {
-//         return total_valence / count as f64;
-//     }
-//     0f64
-// }
-
-fn special_idioms_check(_valence: f64, tokens: &[UniCase<&str>], i: usize) -> f64 {
-    assert_eq!(i > 2, true);
-    let mut valence = _valence;
-    let mut end_i = i + 1;
-
-    //if i isn't the last index
-    if tokens.len() - 1 > i {
-        //make the end of the window 2 words ahead, or until the end of the tokens
-        end_i = min(i + 3, tokens.len());
-    }
-
-    // TODO: We can do this faster by comparing splits?
-    let target_window = tokens[(i - 3)..end_i].iter().map(|u| u.as_ref()).collect::<Vec<&str>>().join(" ").to_lowercase();
-
-    for (key, val) in SPECIAL_CASE_IDIOMS.iter() {
-        if target_window.contains(key.as_ref()) {
-            valence = *val;
-            break;
-        }
-    }
-    let prev_three = tokens[(i - 3)..i].iter().map(|u| u.as_ref()).collect::<Vec<&str>>().join(" ").to_lowercase();
-    for (key, val) in BOOSTER_DICT.iter() {
-        if prev_three.contains(key.as_ref()) {
-            valence += *val;
+// If "but" or another contrast is in the tokens, scales down the sentiment of words before it
+// and adds more emphasis to the words after
+fn but_check(tokens: &[UniCase<&str>], sentiments: &mut [f64], contrasts: &ContrastTable, config: &VaderConfig) {
+    if let Some(but_index) = tokens.iter().position(|s| contrasts.contains(s)) {
+        for i in 0..sentiments.len() {
+            let sentiment = sentiments[i];
+            // The original looks each valence up with `list.index`, so a valence equal to an
+            // earlier one scales the earlier token instead, possibly for a second time
+            let i = match config.compat {
+                Compat::Python => sentiments.iter().position(|&s| s == sentiment).unwrap_or(i),
+                Compat::Legacy | Compat::Corrected => i,
+            };
+            if i < but_index {
+                sentiments[i] = sentiment * config.but_before_weight;
+            } else if i > but_index {
+                sentiments[i] = sentiment * config.but_after_weight;
+            }
         }
     }
-    valence
 }
 
 pub mod demo;
@@ -1,5 +0,0 @@
-extern crate vader_sentiment;
-
-fn main() {
-    vader_sentiment::demo::run_demo();
-}
@@ -0,0 +1,49 @@
+#!/usr/bin/env python3
+"""
+Writes the lexicon.txt of every language pack from sources.tsv.
+
+Each word in sources.tsv takes the mean valence of the English vader_lexicon.txt word it
+translates, so the packs carry the original human ratings over rather than inventing new ones.
+Run it from this directory after editing sources.tsv:
+
+    python3 build_lexicons.py
+"""
+
+import os
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+
+def read_english():
+    means = {}
+    with open(os.path.join(HERE, "..", "vader_lexicon.txt"), encoding="utf-8") as f:
+        for line in f:
+            columns = line.rstrip("\n").split("\t")
+            if len(columns) >= 2:
+                means.setdefault(columns[0], columns[1])
+    return means
+
+
+def main():
+    english = read_english()
+    packs = {}
+    with open(os.path.join(HERE, "sources.tsv"), encoding="utf-8") as f:
+        for line_no, line in enumerate(f, 1):
+            if not line.strip() or line.startswith("#"):
+                continue
+            language, word, source = line.rstrip("\n").split("\t")
+            if source not in english:
+                raise SystemExit("line %d: %r is not in vader_lexicon.txt" % (line_no, source))
+            pack = packs.setdefault(language, {})
+            if word in pack:
+                raise SystemExit("line %d: %r is listed twice for %s" % (line_no, word, language))
+            pack[word] = english[source]
+
+    for language, words in sorted(packs.items()):
+        with open(os.path.join(HERE, language, "lexicon.txt"), "w", encoding="utf-8") as f:
+            for word in sorted(words):
+                f.write("%s\t%s\n" % (word, words[word]))
+
+
+if __name__ == "__main__":
+    main()
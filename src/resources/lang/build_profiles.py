@@ -0,0 +1,49 @@
+#!/usr/bin/env python3
+"""
+Writes the character trigram profiles used for language detection from the texts in samples/.
+
+Words are lowercased runs of letters padded with "_", so "Der Film" gives "_de", "der", "er_",
+"_fi", "fil", "ilm" and "lm_". A profile lists a language's most common trigrams, most common
+first. Run it from this directory after editing a sample:
+
+    python3 build_profiles.py
+"""
+
+import collections
+import os
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+# Trigrams kept per language
+PROFILE_SIZE = 1000
+
+
+def trigrams(text):
+    counts = collections.Counter()
+    word = ""
+    for char in text.lower() + " ":
+        if char.isalpha():
+            word += char
+        elif word:
+            padded = "_" + word + "_"
+            for i in range(len(padded) - 2):
+                counts[padded[i:i + 3]] += 1
+            word = ""
+    return counts
+
+
+def main():
+    samples = os.path.join(HERE, "samples")
+    os.makedirs(os.path.join(HERE, "profiles"), exist_ok=True)
+    for name in sorted(os.listdir(samples)):
+        language, _ = os.path.splitext(name)
+        with open(os.path.join(samples, name), encoding="utf-8") as f:
+            counts = trigrams(f.read())
+        ranked = sorted(counts.items(), key=lambda item: (-item[1], item[0]))[:PROFILE_SIZE]
+        with open(os.path.join(HERE, "profiles", language + ".txt"), "w", encoding="utf-8") as f:
+            for trigram, _ in ranked:
+                f.write(trigram + "\n")
+
+
+if __name__ == "__main__":
+    main()
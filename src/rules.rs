@@ -0,0 +1,781 @@
+/*!
+ * The heuristics that turn lexicon valences into the valences that are summed into a score.
+ * An analyzer runs the rules of its `RulePipeline` in order, and the default pipeline holds
+ * the rules of the original implementation.
+ **/
+
+use std::cmp::min;
+use std::fmt;
+use std::sync::Arc;
+use unicase::UniCase;
+
+use config::{Compat, VaderConfig};
+use explain::{record, Log, Rule, TokenTrace};
+use idioms::{IdiomMatch, IdiomTable};
+use tables::{BoosterTable, ContrastTable, NegationTable, SpecialIdiomTable};
+use {but_check, is_emphasized, ParsedText, SentimentIntensityAnalyzer, PYTHON_SPECIAL_CASE_IDIOMS};
+use {STATIC_AT, STATIC_DOUBT, STATIC_LEAST, STATIC_NEVER, STATIC_NO, STATIC_NOR, STATIC_OR, STATIC_SO,
+     STATIC_THIS, STATIC_VERY, STATIC_WITHOUT};
+
+/**
+ * When a rule runs. For every lexicon word, the `Word` and `Preceding` rules run in pipeline
+ * order, and each run of consecutive `Preceding` rules is applied for the token one, two and
+ * three before the word in turn, skipping tokens that are lexicon words themselves. `Text`
+ * rules run in pipeline order once every token has its valence.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Word,
+    Preceding,
+    Text,
+}
+
+/**
+ * A heuristic that changes valences, such as a negation flipping the word after it
+ **/
+pub trait ValenceRule: Send + Sync {
+    // Identifies the rule in a pipeline, ie "negation"
+    fn name(&self) -> &str;
+
+    fn stage(&self) -> Stage;
+
+    // Changes the valence of a lexicon word, for `Word` and `Preceding` rules
+    fn adjust_word(&self, _context: &RuleContext, _word: &mut WordValence) {}
+
+    // Changes the valences of all tokens, for `Text` rules
+    fn adjust_text(&self, _context: &RuleContext, _text: &mut TextValences) {}
+}
+
+/**
+ * The text being scored and the analyzer scoring it, as rules see them
+ **/
+pub struct RuleContext<'c> {
+    analyzer: &'c SentimentIntensityAnalyzer<'c>,
+    parsed: &'c ParsedText<'c>,
+}
+
+impl<'c> RuleContext<'c> {
+    pub(crate) fn new(analyzer: &'c SentimentIntensityAnalyzer<'c>, parsed: &'c ParsedText<'c>) -> RuleContext<'c> {
+        RuleContext { analyzer, parsed }
+    }
+
+    pub(crate) fn tokens(&self) -> &[UniCase<&'c str>] {
+        &self.parsed.tokens
+    }
+
+    pub fn token_count(&self) -> usize {
+        self.parsed.tokens.len()
+    }
+
+    pub fn token(&self, i: usize) -> &str {
+        &self.parsed.tokens[i]
+    }
+
+    // The lexicon valence of a word, if it is a lexicon word
+    pub fn valence(&self, word: &str) -> Option<f64> {
+        self.analyzer.lexicon.get(&UniCase::new(word))
+    }
+
+    // Whether the text has both ALL CAPS and other words, so that caps are emphasis
+    pub fn has_mixed_caps(&self) -> bool {
+        self.parsed.has_mixed_caps
+    }
+
+    pub fn is_elongated(&self, i: usize) -> bool {
+        self.parsed.is_elongated(i)
+    }
+
+    pub fn config(&self) -> &VaderConfig {
+        &self.analyzer.config
+    }
+
+    pub fn idioms(&self) -> &IdiomTable {
+        &self.analyzer.idioms
+    }
+
+    pub fn special_idioms(&self) -> &SpecialIdiomTable {
+        &self.analyzer.special_idioms
+    }
+
+    pub fn boosters(&self) -> &BoosterTable {
+        &self.analyzer.boosters
+    }
+
+    pub fn negations(&self) -> &NegationTable {
+        &self.analyzer.negations
+    }
+
+    pub fn contrasts(&self) -> &ContrastTable {
+        &self.analyzer.contrasts
+    }
+
+    fn is_lexicon_word(&self, i: usize) -> bool {
+        self.analyzer.lexicon.contains_key(&self.parsed.tokens[i])
+    }
+}
+
+/**
+ * The valence of a lexicon word while the `Word` and `Preceding` rules change it
+ **/
+pub struct WordValence<'l> {
+    index: usize,
+    distance: usize,
+    lexicon_valence: f64,
+    valence: f64,
+    log: Log<'l>,
+}
+
+impl<'l> WordValence<'l> {
+    pub(crate) fn new(index: usize, lexicon_valence: f64, log: Log<'l>) -> WordValence<'l> {
+        WordValence { index, distance: 0, lexicon_valence, valence: lexicon_valence, log }
+    }
+
+    // Index of the word in the tokens
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    // How many tokens before the word the token a `Preceding` rule looks at is, 0 for `Word` rules
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    pub fn lexicon_valence(&self) -> f64 {
+        self.lexicon_valence
+    }
+
+    pub fn valence(&self) -> f64 {
+        self.valence
+    }
+
+    // Changes the valence, and records the change as `rule` when the score is explained
+    pub fn set(&mut self, rule: Rule, trigger: Option<usize>, valence: f64) {
+        record(&mut self.log, rule, trigger, self.valence, valence);
+        self.valence = valence;
+    }
+}
+
+/**
+ * The valences of all tokens while the `Text` rules change them
+ **/
+pub struct TextValences<'v> {
+    valences: &'v mut [f64],
+    traces: Option<&'v mut [TokenTrace]>,
+    idioms: Vec<IdiomMatch>,
+    punctuation_amplifier: f64,
+}
+
+impl<'v> TextValences<'v> {
+    pub(crate) fn new(valences: &'v mut [f64], traces: Option<&'v mut [TokenTrace]>) -> TextValences<'v> {
+        TextValences { valences, traces, idioms: Vec::new(), punctuation_amplifier: 0f64 }
+    }
+
+    pub fn valences(&self) -> &[f64] {
+        self.valences
+    }
+
+    // Changes the valence of token i, and records the change as `rule` when the score is explained
+    pub fn set(&mut self, i: usize, rule: Rule, trigger: Option<usize>, valence: f64) {
+        if let Some(ref mut traces) = self.traces {
+            record(&mut Some(&mut traces[i].adjustments), rule, trigger, self.valences[i], valence);
+        }
+        self.valences[i] = valence;
+    }
+
+    // Emphasis for '!' and '?' that pushes the total away from zero, 0 unless a rule sets it
+    pub fn punctuation_amplifier(&self) -> f64 {
+        self.punctuation_amplifier
+    }
+
+    pub fn set_punctuation_amplifier(&mut self, amplifier: f64) {
+        self.punctuation_amplifier = amplifier;
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<IdiomMatch>, f64) {
+        (self.idioms, self.punctuation_amplifier)
+    }
+}
+
+/**
+ * The rules an analyzer applies, in order. `RulePipeline::default()` reproduces the scores of
+ * the original implementation. Rules are found by name to remove them or insert others around
+ * them:
+ *
+ * "no", "no_negation", "caps_emphasis", "elongation", "booster", "never_intensifier",
+ * "negation", "special_idioms", "least", "laden_idioms", "but_contrast", "punctuation"
+ **/
+#[derive(Clone)]
+pub struct RulePipeline {
+    rules: Vec<Arc<dyn ValenceRule>>,
+}
+
+impl Default for RulePipeline {
+    fn default() -> RulePipeline {
+        let mut pipeline = RulePipeline::empty();
+        pipeline.push(NoModifier);
+        pipeline.push(NoNegation);
+        pipeline.push(CapsEmphasis);
+        pipeline.push(Elongation);
+        pipeline.push(BoosterScaling);
+        pipeline.push(NeverIntensifier);
+        pipeline.push(Negation);
+        pipeline.push(SpecialIdioms);
+        pipeline.push(LeastNegation);
+        pipeline.push(LadenIdioms);
+        pipeline.push(ButContrast);
+        pipeline.push(PunctuationEmphasis);
+        pipeline
+    }
+}
+
+impl fmt::Debug for RulePipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl RulePipeline {
+    pub fn empty() -> RulePipeline {
+        RulePipeline { rules: Vec::new() }
+    }
+
+    pub fn push<R: ValenceRule + 'static>(&mut self, rule: R) {
+        self.rules.push(Arc::new(rule));
+    }
+
+    pub fn insert<R: ValenceRule + 'static>(&mut self, index: usize, rule: R) {
+        self.rules.insert(index, Arc::new(rule));
+    }
+
+    // Index of the first rule with the name
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.name() == name)
+    }
+
+    // Removes the first rule with the name, returns whether there was one
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(index) => {
+                self.rules.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.name())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    //Runs the Word and Preceding rules on the lexicon word at word.index
+    pub(crate) fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        let mut r = 0;
+        while r < self.rules.len() {
+            match self.rules[r].stage() {
+                Stage::Word => {
+                    self.rules[r].adjust_word(context, word);
+                    r += 1;
+                },
+                Stage::Text => r += 1,
+                Stage::Preceding => {
+                    let end = self.rules[r..].iter().position(|rule| rule.stage() == Stage::Word)
+                        .map_or(self.rules.len(), |n| r + n);
+                    for distance in 1..4 {
+                        if word.index >= distance && !context.is_lexicon_word(word.index - distance) {
+                            word.distance = distance;
+                            for rule in self.rules[r..end].iter().filter(|rule| rule.stage() == Stage::Preceding) {
+                                rule.adjust_word(context, word);
+                            }
+                        }
+                    }
+                    word.distance = 0;
+                    r = end;
+                },
+            }
+        }
+    }
+
+    pub(crate) fn adjust_text(&self, context: &RuleContext, text: &mut TextValences) {
+        for rule in self.rules.iter().filter(|rule| rule.stage() == Stage::Text) {
+            rule.adjust_text(context, text);
+        }
+    }
+}
+
+/**
+ * "no" right before another lexicon word negates that word rather than counting itself.
+ * Only vaderSentiment 3.3.2 has this rule, so it only applies in `Compat::Python` mode.
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoModifier;
+
+impl ValenceRule for NoModifier {
+    fn name(&self) -> &str {
+        "no"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Word
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        if context.config().compat != Compat::Python {
+            return;
+        }
+        let (tokens, i) = (context.tokens(), word.index);
+        if tokens[i] == *STATIC_NO && i + 1 < tokens.len() && context.is_lexicon_word(i + 1) {
+            word.set(Rule::Modifier, None, 0f64);
+        }
+    }
+}
+
+/**
+ * "no" in one of the two tokens before a word, or three before it with an "or" or "nor" in
+ * between as in "no fun or joy", negates the word's lexicon valence. Like `NoModifier`, it only
+ * applies in `Compat::Python` mode.
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoNegation;
+
+impl ValenceRule for NoNegation {
+    fn name(&self) -> &str {
+        "no_negation"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Word
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        if context.config().compat != Compat::Python {
+            return;
+        }
+        let (tokens, i) = (context.tokens(), word.index);
+        let no = if i > 0 && tokens[i - 1] == *STATIC_NO {
+            Some(i - 1)
+        } else if i > 1 && tokens[i - 2] == *STATIC_NO {
+            Some(i - 2)
+        } else if i > 2 && tokens[i - 3] == *STATIC_NO && (tokens[i - 1] == *STATIC_OR || tokens[i - 1] == *STATIC_NOR) {
+            Some(i - 3)
+        } else {
+            None
+        };
+        if no.is_some() {
+            let negated = word.lexicon_valence * context.config().negation_scalar;
+            word.set(Rule::Negation, no, negated);
+        }
+    }
+}
+
+// Pushes a valence away from zero by amount
+fn emphasize(valence: f64, amount: f64) -> f64 {
+    if valence > 0f64 {
+        valence + amount
+    } else {
+        valence - amount
+    }
+}
+
+/**
+ * An ALL CAPS word in text that also has other words is emphasized by `c_incr`
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CapsEmphasis;
+
+impl ValenceRule for CapsEmphasis {
+    fn name(&self) -> &str {
+        "caps_emphasis"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Word
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        if is_emphasized(context.token(word.index), context.config()) && context.has_mixed_caps() {
+            let emphasized = emphasize(word.valence, context.config().c_incr);
+            word.set(Rule::CapsEmphasis, None, emphasized);
+        }
+    }
+}
+
+/**
+ * An elongated word like "goooood" is emphasized by `elongation_incr`
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Elongation;
+
+impl ValenceRule for Elongation {
+    fn name(&self) -> &str {
+        "elongation"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Word
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        if context.is_elongated(word.index) {
+            let emphasized = emphasize(word.valence, context.config().elongation_incr);
+            word.set(Rule::Elongation, None, emphasized);
+        }
+    }
+}
+
+/**
+ * A booster or dampener before a word scales it, less the further away it is. A booster in
+ * caps or elongated itself scales more.
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BoosterScaling;
+
+impl ValenceRule for BoosterScaling {
+    fn name(&self) -> &str {
+        "booster"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Preceding
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        let booster = word.index - word.distance;
+        let mut scalar = scalar_inc_dec(&context.tokens()[booster], word.valence, context.has_mixed_caps(),
+                                        context.is_elongated(booster), context.boosters(), context.config());
+        if word.distance == 2 {
+            scalar *= 0.95;
+        } else if word.distance == 3 {
+            scalar *= 0.9
+        }
+        let boosted = word.valence + scalar;
+        word.set(Rule::Booster, Some(booster), boosted);
+    }
+}
+
+//Checks how previous tokens affect the valence of the current token
+fn scalar_inc_dec(token: &UniCase<&str>, valence: f64, has_mixed_caps: bool, elongated: bool,
+                  boosters: &BoosterTable, config: &VaderConfig) -> f64 {
+    let mut scalar = 0.0;
+    if let Some(booster) = boosters.get(token) {
+        scalar = booster.scalar(config);
+        if valence < 0.0 {
+            scalar *= -1.0;
+        }
+        if is_emphasized(token, config) && has_mixed_caps {
+            if valence > 0.0 {
+                scalar += config.c_incr;
+            } else {
+                scalar -= config.c_incr;
+            }
+        }
+        if elongated {
+            if valence > 0.0 {
+                scalar += config.elongation_incr;
+            } else {
+                scalar -= config.elongation_incr;
+            }
+        }
+    }
+    scalar
+}
+
+//Whether "never so" or "never this" ends distance tokens before word i, so it intensifies i
+fn never_intensifies(tokens: &[UniCase<&str>], i: usize, distance: usize, config: &VaderConfig) -> bool {
+    let so_or_this = |token: &UniCase<&str>| *token == *STATIC_SO || *token == *STATIC_THIS;
+    match distance {
+        2 => tokens[i - 2] == *STATIC_NEVER && so_or_this(&tokens[i - 1]),
+        3 => {
+            let never = tokens[i - 3] == *STATIC_NEVER;
+            // Python and legacy scoring let "so" or "this" right before the word intensify it
+            // without any "never", and legacy scoring also does for a "this" two tokens before it
+            match config.compat {
+                Compat::Python => never && so_or_this(&tokens[i - 2]) || so_or_this(&tokens[i - 1]),
+                Compat::Legacy => never && tokens[i - 2] == *STATIC_SO || tokens[i - 2] == *STATIC_THIS ||
+                                  so_or_this(&tokens[i - 1]),
+                Compat::Corrected => never && (so_or_this(&tokens[i - 2]) || so_or_this(&tokens[i - 1])),
+            }
+        },
+        _ => false,
+    }
+}
+
+//Whether "without doubt" starts distance tokens before word i. Legacy scoring also accepts a
+//"doubt" right before the word without any "without".
+fn without_doubt(tokens: &[UniCase<&str>], i: usize, distance: usize, config: &VaderConfig) -> bool {
+    match distance {
+        2 => tokens[i - 2] == *STATIC_WITHOUT && tokens[i - 1] == *STATIC_DOUBT,
+        3 => {
+            let without = tokens[i - 3] == *STATIC_WITHOUT;
+            match config.compat {
+                Compat::Python | Compat::Corrected => without && (tokens[i - 2] == *STATIC_DOUBT ||
+                                                                  tokens[i - 1] == *STATIC_DOUBT),
+                Compat::Legacy => without && tokens[i - 2] == *STATIC_DOUBT || tokens[i - 1] == *STATIC_DOUBT,
+            }
+        },
+        _ => false,
+    }
+}
+
+/**
+ * "never so" or "never this" before a word intensifies it instead of negating it
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NeverIntensifier;
+
+impl ValenceRule for NeverIntensifier {
+    fn name(&self) -> &str {
+        "never_intensifier"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Preceding
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        if never_intensifies(context.tokens(), word.index, word.distance, context.config()) {
+            let intensified = word.valence * 1.25;
+            word.set(Rule::NeverIntensifier, Some(word.index - word.distance), intensified);
+        }
+    }
+}
+
+/**
+ * A negation before a word scales it by `negation_scalar`, unless it is part of "never so",
+ * "never this" or "without doubt"
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Negation;
+
+impl ValenceRule for Negation {
+    fn name(&self) -> &str {
+        "negation"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Preceding
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        let (tokens, i, distance) = (context.tokens(), word.index, word.distance);
+        if never_intensifies(tokens, i, distance, context.config()) || without_doubt(tokens, i, distance, context.config()) {
+            return;
+        }
+        let negator = i - distance;
+        if context.negations().is_negation(&tokens[negator]) {
+            let negated = word.valence * context.config().negation_scalar;
+            word.set(Rule::Negation, Some(negator), negated);
+        }
+    }
+}
+
+/**
+ * A special case idiom around a word ("the bomb", "yeah right", ...) overrides its valence,
+ * and a multi-word booster right before it ("kind of") scales it
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpecialIdioms;
+
+impl ValenceRule for SpecialIdioms {
+    fn name(&self) -> &str {
+        "special_idioms"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Preceding
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        if word.distance != 3 {
+            return;
+        }
+        match context.config().compat {
+            Compat::Legacy => special_idioms_in_window(context, word),
+            Compat::Python | Compat::Corrected => special_idioms_in_n_grams(context, word),
+        }
+    }
+}
+
+//Finds idioms anywhere in the window from three tokens before the word to two after it, and
+//boosters anywhere in the three tokens before it
+fn special_idioms_in_window(context: &RuleContext, word: &mut WordValence) {
+    let (tokens, i) = (context.tokens(), word.index);
+    let end_i = if tokens.len() - 1 > i { min(i + 3, tokens.len()) } else { i + 1 };
+    let window = |start: usize, end: usize| {
+        tokens[start..end].iter().map(|u| u.as_ref()).collect::<Vec<&str>>().join(" ").to_lowercase()
+    };
+
+    let target_window = window(i - 3, end_i);
+    if let Some((_, val)) = context.special_idioms().iter().find(|&(key, _)| target_window.contains(key)) {
+        word.set(Rule::SpecialIdiom, None, val);
+    }
+    let prev_three = window(i - 3, i);
+    for (key, booster) in context.boosters().iter() {
+        if prev_three.contains(key) {
+            let boosted = word.valence + booster.scalar(context.config());
+            word.set(Rule::Booster, None, boosted);
+        }
+    }
+}
+
+//Matches idioms and boosters against the exact n-grams around the word, as the original does
+fn special_idioms_in_n_grams(context: &RuleContext, word: &mut WordValence) {
+    let (tokens, i) = (context.tokens(), word.index);
+    let n_gram = |start: usize, end: usize| {
+        tokens[start..end].iter().map(|u| u.as_ref()).collect::<Vec<&str>>().join(" ").to_lowercase()
+    };
+    let (three_two_one, three_two, two_one) = (n_gram(i - 3, i), n_gram(i - 3, i - 1), n_gram(i - 2, i));
+
+    // The first idiom that ends at or just before the word wins, but one that starts at the
+    // word overrides it
+    let special_idiom = |phrase: &String| context.special_idioms().get(phrase).or_else(|| {
+        PYTHON_SPECIAL_CASE_IDIOMS.iter().find(|&&(idiom, _)| idiom == phrase).map(|&(_, valence)| valence)
+    });
+    let mut idioms = vec![n_gram(i - 1, i + 1), n_gram(i - 2, i + 1), two_one.clone(), three_two_one.clone(),
+                          three_two.clone()];
+    let before = idioms.iter().filter_map(special_idiom).next();
+    idioms.clear();
+    if tokens.len() - 1 > i {
+        idioms.push(n_gram(i, i + 2));
+    }
+    if tokens.len() - 1 > i + 1 {
+        idioms.push(n_gram(i, i + 3));
+    }
+    let after = idioms.iter().rev().filter_map(special_idiom).next();
+    if let Some(val) = after.or(before) {
+        word.set(Rule::SpecialIdiom, None, val);
+    }
+
+    // Multi-word boosters like "kind of" right before the word
+    for phrase in &[three_two_one, three_two, two_one] {
+        if let Some(booster) = context.boosters().get(phrase) {
+            let boosted = word.valence + booster.scalar(context.config());
+            word.set(Rule::Booster, None, boosted);
+        }
+    }
+}
+
+/**
+ * "least" before a word negates it. Except in `Compat::Legacy` mode, "at least" and "very least"
+ * don't.
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeastNegation;
+
+impl ValenceRule for LeastNegation {
+    fn name(&self) -> &str {
+        "least"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Word
+    }
+
+    fn adjust_word(&self, context: &RuleContext, word: &mut WordValence) {
+        let (tokens, i) = (context.tokens(), word.index);
+        if i == 0 || tokens[i - 1] != *STATIC_LEAST {
+            return;
+        }
+        if context.config().compat != Compat::Legacy && (context.is_lexicon_word(i - 1) ||
+                                                         i > 1 && (tokens[i - 2] == *STATIC_AT || tokens[i - 2] == *STATIC_VERY)) {
+            return;
+        }
+        let negated = word.valence * context.config().negation_scalar;
+        word.set(Rule::Least, Some(i - 1), negated);
+    }
+}
+
+/**
+ * Replaces the valences of the tokens in sentiment laden idioms ("under the weather", ...)
+ * with the idiom's valence, which is carried by the first token of the idiom and negated like
+ * a lexicon word would be. The Python compat mode doesn't have them.
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LadenIdioms;
+
+impl ValenceRule for LadenIdioms {
+    fn name(&self) -> &str {
+        "laden_idioms"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Text
+    }
+
+    fn adjust_text(&self, context: &RuleContext, text: &mut TextValences) {
+        if context.config().compat == Compat::Python {
+            return;
+        }
+        let tokens = context.tokens();
+        let matches = context.idioms().find_matches(tokens);
+        for idiom in &matches {
+            for i in idiom.start..idiom.end {
+                let valence = if i == idiom.start { idiom.valence } else { 0f64 };
+                text.set(i, Rule::LadenIdiom, Some(idiom.start), valence);
+            }
+            let window = idiom.start.saturating_sub(3)..idiom.start;
+            if let Some(negator) = window.rev().find(|&j| context.negations().is_negation(&tokens[j])) {
+                let negated = text.valences[idiom.start] * context.config().negation_scalar;
+                text.set(idiom.start, Rule::Negation, Some(negator), negated);
+            }
+        }
+        text.idioms.extend(matches);
+    }
+}
+
+/**
+ * Dampens the tokens before a contrastive "but", or another word in the analyzer's
+ * `ContrastTable`, by `but_before_weight`, and emphasizes the ones after it by `but_after_weight`
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ButContrast;
+
+impl ValenceRule for ButContrast {
+    fn name(&self) -> &str {
+        "but_contrast"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Text
+    }
+
+    fn adjust_text(&self, context: &RuleContext, text: &mut TextValences) {
+        let tokens = context.tokens();
+        let but_index = match tokens.iter().position(|s| context.contrasts().contains(s)) {
+            Some(but_index) => but_index,
+            None => return,
+        };
+        let mut scaled = text.valences.to_vec();
+        but_check(tokens, &mut scaled, context.contrasts(), context.config());
+        for (i, valence) in scaled.into_iter().enumerate() {
+            text.set(i, Rule::ButContrast, Some(but_index), valence);
+        }
+    }
+}
+
+/**
+ * '!' and '?' in the text emphasize the total, up to `max_emark` and `max_qmark` of them
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PunctuationEmphasis;
+
+impl ValenceRule for PunctuationEmphasis {
+    fn name(&self) -> &str {
+        "punctuation"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Text
+    }
+
+    fn adjust_text(&self, context: &RuleContext, text: &mut TextValences) {
+        text.set_punctuation_amplifier(context.parsed.punc_amplifier);
+    }
+}
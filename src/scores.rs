@@ -0,0 +1,144 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/**
+ * The result of scoring a piece of text. `neg`, `neu` and `pos` are the proportions of the
+ * text that fall in each category, `compound` is the normalized, weighted composite score
+ * between -1.0 (most negative) and 1.0 (most positive).
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SentimentScores {
+    pub neg: f64,
+    pub neu: f64,
+    pub pos: f64,
+    pub compound: f64,
+}
+
+/**
+ * Coarse classification of a compound score
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SentimentLabel {
+    Positive,
+    Neutral,
+    Negative,
+}
+
+/**
+ * Compound score cutoffs used to classify scores. A compound score at or above `positive` is
+ * positive, at or below `negative` is negative, anything in between is neutral.
+ * The defaults are the ones recommended by the original authors.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LabelThresholds {
+    pub positive: f64,
+    pub negative: f64,
+}
+
+impl LabelThresholds {
+    pub fn new(positive: f64, negative: f64) -> LabelThresholds {
+        LabelThresholds { positive, negative }
+    }
+
+    pub fn classify(&self, compound: f64) -> SentimentLabel {
+        if compound >= self.positive {
+            SentimentLabel::Positive
+        } else if compound <= self.negative {
+            SentimentLabel::Negative
+        } else {
+            SentimentLabel::Neutral
+        }
+    }
+}
+
+impl Default for LabelThresholds {
+    fn default() -> LabelThresholds {
+        LabelThresholds::new(0.05, -0.05)
+    }
+}
+
+impl SentimentScores {
+    //Classifies the scores using the default thresholds
+    pub fn label(&self) -> SentimentLabel {
+        self.label_with(&LabelThresholds::default())
+    }
+
+    pub fn label_with(&self, thresholds: &LabelThresholds) -> SentimentLabel {
+        thresholds.classify(self.compound)
+    }
+
+    //Builds the "neg"/"neu"/"pos"/"compound" map returned by earlier versions of this crate
+    pub fn to_map(&self) -> HashMap<&'static str, f64> {
+        hashmap!["neg" => self.neg,
+                 "neu" => self.neu,
+                 "pos" => self.pos,
+                 "compound" => self.compound]
+    }
+}
+
+impl From<SentimentScores> for HashMap<&'static str, f64> {
+    fn from(scores: SentimentScores) -> HashMap<&'static str, f64> {
+        scores.to_map()
+    }
+}
+
+impl<'a, S> TryFrom<&'a HashMap<S, f64>> for SentimentScores
+    where S: Borrow<str> + Hash + Eq {
+    type Error = MissingScoreError;
+
+    fn try_from(map: &'a HashMap<S, f64>) -> Result<SentimentScores, MissingScoreError> {
+        let get = |key: &'static str| map.get(key).cloned().ok_or(MissingScoreError { key });
+        Ok(SentimentScores {
+            neg: get("neg")?,
+            neu: get("neu")?,
+            pos: get("pos")?,
+            compound: get("compound")?,
+        })
+    }
+}
+
+/**
+ * Returned when converting a legacy score map that is missing one of its keys
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingScoreError {
+    pub key: &'static str,
+}
+
+impl fmt::Display for MissingScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "score map is missing the \"{}\" key", self.key)
+    }
+}
+
+impl Error for MissingScoreError {}
+
+/**
+ * How much a compound score could move given how much the human raters disagreed on the
+ * words that produced it. The interval covers roughly 95% of the valences the raters gave,
+ * propagated through the same rules as the score itself.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Uncertainty {
+    // Standard deviation of the summed valence of the text
+    pub std_dev: f64,
+    pub compound_low: f64,
+    pub compound_high: f64,
+    // Number of sentiment-bearing words with no standard deviation in the lexicon
+    pub unrated_words: usize,
+}
+
+impl Uncertainty {
+    pub fn width(&self) -> f64 {
+        self.compound_high - self.compound_low
+    }
+}
@@ -0,0 +1,187 @@
+use scores::SentimentScores;
+
+// Words that usually end with a period without ending the sentence
+static ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "rev", "gov", "sen", "rep", "capt", "lt",
+    "col", "sgt", "vs", "etc", "e.g", "i.e", "cf", "approx", "inc", "ltd", "corp", "dept", "vol", "pp", "ch",
+    "jan", "feb", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec", "mon", "tue", "wed", "thu",
+    "fri", "a.m", "p.m", "u.s", "u.k"];
+
+// Abbreviations that are also ordinary words, like "no" or "sat". They only count as abbreviations
+// when capitalized and followed by a number, as in "No. 5", "Fig. 3" or "Sat. 12"
+static WORD_ABBREVIATIONS: &[&str] = &["no", "fig", "est", "gen", "co", "ed", "al", "mar", "sat", "sun"];
+
+// Punctuation that can end a sentence, including the ellipsis and fullwidth forms
+const TERMINATORS: &[char] = &['.', '!', '?', '\u{2026}', '\u{203C}', '\u{2047}', '\u{2048}', '\u{2049}',
+                               '\u{FF01}', '\u{FF1F}', '\u{3002}'];
+
+// Closing quotes and brackets that can follow the punctuation ending a sentence
+const CLOSERS: &[char] = &['"', '\'', ')', ']', '}', '\u{201D}', '\u{2019}', '\u{BB}'];
+
+/**
+ * A sentence found by `split_sentences`, with its byte offsets in the text
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sentence<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/**
+ * Splits text into sentences. A sentence ends at '!' or '?', at a period or ellipsis that is
+ * followed by a word that doesn't start in lowercase, at an emoticon that is followed by a
+ * capitalized word, or at a blank line. Periods after abbreviations ("Dr.", "e.g.") and
+ * initials ("J. R. R. Tolkien") don't end sentences, and emoticons like "o.O" are never split.
+ **/
+pub fn split_sentences(text: &str) -> Vec<Sentence<'_>> {
+    let words = words(text);
+    let mut sentences = Vec::new();
+    let mut start = None;
+    for (i, &(word_start, word_end)) in words.iter().enumerate() {
+        let sentence_start = *start.get_or_insert(word_start);
+        let next = words.get(i + 1);
+        let ends_sentence = match next {
+            None => true,
+            Some(&(next_start, _)) => {
+                is_paragraph_break(&text[word_end..next_start]) ||
+                    ends_with_terminator(&text[word_start..word_end], &text[next_start..])
+            },
+        };
+        if ends_sentence {
+            sentences.push(Sentence { text: &text[sentence_start..word_end], start: sentence_start, end: word_end });
+            start = None;
+        }
+    }
+    sentences
+}
+
+// Byte spans of the whitespace separated words of the text
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, chr) in text.char_indices() {
+        match (chr.is_whitespace(), start) {
+            (true, Some(word_start)) => {
+                words.push((word_start, i));
+                start = None;
+            },
+            (false, None) => start = Some(i),
+            _ => {},
+        }
+    }
+    if let Some(word_start) = start {
+        words.push((word_start, text.len()));
+    }
+    words
+}
+
+fn is_paragraph_break(space: &str) -> bool {
+    space.matches('\n').count() > 1
+}
+
+//Checks if a word ends its sentence, given the rest of the text after it
+fn ends_with_terminator(word: &str, rest: &str) -> bool {
+    let next_is_lowercase = rest.chars().next().is_some_and(char::is_lowercase);
+    if is_emoticon(word) {
+        return rest.chars().next().is_some_and(char::is_uppercase);
+    }
+    let core = word.trim_end_matches(CLOSERS);
+    let stem = core.trim_end_matches(TERMINATORS);
+    let terminators = &core[stem.len()..];
+    if terminators.is_empty() {
+        false
+    } else if terminators.chars().any(|chr| chr != '.' && chr != '\u{2026}') {
+        true
+    } else if terminators == "." {
+        !(is_abbreviation(stem, rest) || next_is_lowercase)
+    } else {
+        // Ellipses trail off within a sentence unless a new one starts after them
+        !next_is_lowercase
+    }
+}
+
+// Emoticons are lexicon words like ":-/" or "o.O", or other runs of punctuation like ":)))"
+fn is_emoticon(word: &str) -> bool {
+    let has_punctuation = word.chars().any(|chr| chr.is_ascii_punctuation());
+    let only_terminators = word.chars().all(|chr| TERMINATORS.contains(&chr) || CLOSERS.contains(&chr));
+    has_punctuation && !only_terminators &&
+        (!word.chars().any(char::is_alphanumeric) || ::LEXICON.contains_key(&::UniCase::new(word)))
+}
+
+fn is_abbreviation(stem: &str, rest: &str) -> bool {
+    let stem = stem.trim_start_matches(&['"', '\'', '(', '[', '\u{201C}', '\u{2018}'][..]);
+    let lowercase = stem.to_lowercase();
+    // Initials like "J." and acronyms like "U.S.A."
+    let initials = !stem.is_empty() && stem.split('.').all(|part| part.chars().count() == 1 &&
+                                                                    part.chars().all(char::is_alphabetic));
+    let capitalized = stem.chars().next().is_some_and(char::is_uppercase);
+    let before_number = rest.chars().next().is_some_and(|chr| chr.is_ascii_digit());
+    initials || ABBREVIATIONS.contains(&lowercase.as_str()) ||
+        (capitalized && before_number && WORD_ABBREVIATIONS.contains(&lowercase.as_str()))
+}
+
+/**
+ * The scores of one sentence of a document
+ **/
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SentenceScores {
+    pub text: String,
+    // Byte offsets of the sentence in the document
+    pub start: usize,
+    pub end: usize,
+    // Number of tokens scored, used to weight the sentence
+    pub tokens: usize,
+    pub scores: SentimentScores,
+}
+
+/**
+ * Per-sentence scores of a document along with aggregates of their compound scores.
+ * All aggregates are 0.0 (or None) for a document without sentences.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DocumentScores {
+    pub sentences: Vec<SentenceScores>,
+    pub mean_compound: f64,
+    // Mean compound score weighted by the number of tokens in each sentence
+    pub weighted_compound: f64,
+    // Index of the sentences with the highest and lowest compound score
+    pub max: Option<usize>,
+    pub min: Option<usize>,
+}
+
+impl DocumentScores {
+    pub(crate) fn new(sentences: Vec<SentenceScores>) -> DocumentScores {
+        let count = sentences.len() as f64;
+        let total_tokens: usize = sentences.iter().map(|s| s.tokens).sum();
+        let compounds = sentences.iter().map(|s| s.scores.compound);
+        let mean_compound = if sentences.is_empty() { 0.0 } else { compounds.clone().sum::<f64>() / count };
+        let weighted_compound = if total_tokens == 0 {
+            0.0
+        } else {
+            sentences.iter().map(|s| s.scores.compound * s.tokens as f64).sum::<f64>() / total_tokens as f64
+        };
+        // The first sentence wins ties
+        let mut max: Option<usize> = None;
+        let mut min: Option<usize> = None;
+        for (i, compound) in compounds.enumerate() {
+            if max.is_none_or(|j| compound > sentences[j].scores.compound) {
+                max = Some(i);
+            }
+            if min.is_none_or(|j| compound < sentences[j].scores.compound) {
+                min = Some(i);
+            }
+        }
+        DocumentScores { sentences, mean_compound, weighted_compound, max, min }
+    }
+
+    pub fn max_sentence(&self) -> Option<&SentenceScores> {
+        self.max.map(|i| &self.sentences[i])
+    }
+
+    pub fn min_sentence(&self) -> Option<&SentenceScores> {
+        self.min.map(|i| &self.sentences[i])
+    }
+}
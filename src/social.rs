@@ -0,0 +1,137 @@
+use is_punctuation;
+use tokenizer::{word_token, Token, Tokenizer};
+
+// Longest hashtag (in chars) that is segmented by looking up every substring
+const MAX_SEGMENTED_HASHTAG: usize = 40;
+
+/**
+ * Tokenizes social media text. URLs, email addresses and @mentions are kept whole, so they
+ * can't match lexicon words and stay neutral. Hashtags are split into the words they are made
+ * of, by their case ("#NotHappy"), underscores ("#not_happy") or, for a hashtag in a single
+ * case, into words that `is_known` accepts ("#nothappy"). Anything else is tokenized like the
+ * default tokenizer does.
+ **/
+pub(crate) struct SocialMediaTokenizer<F> {
+    pub is_known: F,
+}
+
+impl<F: Fn(&str) -> bool + Send + Sync> Tokenizer for SocialMediaTokenizer<F> {
+    fn tokenize<'t>(&self, text: &'t str, tokens: &mut Vec<Token<'t>>) {
+        for word in text.split_whitespace().filter(|s| s.chars().count() > 1) {
+            // Punctuation around a word is trimmed, except for the '@' or '#' it starts with
+            let trimmed = word.trim_start_matches(|chr| chr != '@' && chr != '#' && is_punctuation(chr))
+                              .trim_end_matches(is_punctuation);
+            if is_url(trimmed) || is_email(trimmed) || is_mention(trimmed) {
+                tokens.push(Token::from_slice(text, trimmed));
+            } else if is_hashtag(trimmed) {
+                let words = segment_hashtag(&trimmed[1..], &self.is_known);
+                tokens.extend(words.into_iter().map(|word| Token::from_slice(text, word)));
+            } else {
+                tokens.push(word_token(text, word));
+            }
+        }
+    }
+}
+
+fn is_url(word: &str) -> bool {
+    let prefix: String = word.chars().take(8).flat_map(char::to_lowercase).collect();
+    (prefix.starts_with("http://") || prefix.starts_with("https://") || prefix.starts_with("www.")) && word.len() > 4
+}
+
+fn is_email(word: &str) -> bool {
+    let mut parts = word.split('@');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(user), Some(domain), None) => {
+            !user.is_empty() && !domain.starts_with('.') && !domain.ends_with('.') && domain.contains('.') &&
+            !user.chars().chain(domain.chars()).any(char::is_whitespace)
+        },
+        _ => false,
+    }
+}
+
+fn is_handle_char(chr: char) -> bool {
+    chr.is_alphanumeric() || chr == '_'
+}
+
+fn is_mention(word: &str) -> bool {
+    word.len() > 1 && word.starts_with('@') && word[1..].chars().all(is_handle_char)
+}
+
+fn is_hashtag(word: &str) -> bool {
+    word.len() > 1 && word.starts_with('#') && word[1..].chars().all(is_handle_char) &&
+    word[1..].chars().any(char::is_alphabetic)
+}
+
+// Splits the text of a hashtag at underscores and case changes, or into known words if it is
+// all in one case
+fn segment_hashtag<'t, F: Fn(&str) -> bool>(tag: &'t str, is_known: &F) -> Vec<&'t str> {
+    let parts = split_parts(tag);
+    if parts.len() == 1 && !is_known(tag) {
+        if let Some(words) = known_words(tag, is_known) {
+            return words;
+        }
+    }
+    parts
+}
+
+// Splits "NotHappy_2day" into "Not", "Happy", "2", "day". A run of capitals followed by a word,
+// as in "NYCRocks", keeps its last capital for the word: "NYC", "Rocks".
+fn split_parts(tag: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    for (i, chr) in tag.char_indices() {
+        let next = tag[i + chr.len_utf8()..].chars().next();
+        let boundary = match prev {
+            None => false,
+            Some(prev) if chr == '_' || prev == '_' => true,
+            Some(prev) => {
+                (prev.is_lowercase() && chr.is_uppercase()) ||
+                (prev.is_alphabetic() != chr.is_alphabetic()) ||
+                (prev.is_uppercase() && chr.is_uppercase() && next.is_some_and(char::is_lowercase))
+            },
+        };
+        if boundary && start < i {
+            parts.push(&tag[start..i]);
+            start = i;
+        }
+        if chr == '_' {
+            start = i + 1;
+        }
+        prev = Some(chr);
+    }
+    if start < tag.len() {
+        parts.push(&tag[start..]);
+    }
+    parts
+}
+
+// Covers the whole tag with the fewest known words of at least two chars, if it can be
+fn known_words<'t, F: Fn(&str) -> bool>(tag: &'t str, is_known: &F) -> Option<Vec<&'t str>> {
+    let bounds: Vec<usize> = tag.char_indices().map(|(i, _)| i).chain(Some(tag.len())).collect();
+    if bounds.len() - 1 > MAX_SEGMENTED_HASHTAG {
+        return None;
+    }
+    // best[end] is the fewest words covering the chars before bounds[end], and where the last starts
+    let mut best: Vec<Option<(usize, usize)>> = vec![None; bounds.len()];
+    best[0] = Some((0, 0));
+    for end in 2..bounds.len() {
+        for start in (0..end - 1).rev() {
+            if let Some((count, _)) = best[start] {
+                let better = best[end].is_none_or(|(best_count, _)| count + 1 < best_count);
+                if better && is_known(&tag[bounds[start]..bounds[end]]) {
+                    best[end] = Some((count + 1, start));
+                }
+            }
+        }
+    }
+    let mut words = Vec::new();
+    let mut end = bounds.len() - 1;
+    while end > 0 {
+        let (_, start) = best[end]?;
+        words.push(&tag[bounds[start]..bounds[end]]);
+        end = start;
+    }
+    words.reverse();
+    Some(words)
+}
@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+/**
+ * Where a token came from in the text passed to the analyzer, as byte offsets (for slicing
+ * the string) and char offsets (for UIs that count characters). Tokens that come from an
+ * emoji's description span the whole emoji.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl Span {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/**
+ * A part of the text that was rewritten, like an emoji replaced by its description (including
+ * the space added before it) or an elongated word replaced by its spelling, with its byte
+ * ranges in the rewritten text and in the original text
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Replacement {
+    pub rewritten: Range<usize>,
+    pub original: Range<usize>,
+}
+
+/**
+ * Maps byte ranges of a rewritten text back to the original text
+ **/
+pub(crate) struct OffsetMap<'t> {
+    original: &'t str,
+    replacements: Vec<Replacement>,
+    // Last byte offset converted to a char offset, so converting spans in order stays linear
+    last_byte: usize,
+    last_char: usize,
+}
+
+impl<'t> OffsetMap<'t> {
+    pub fn new(original: &'t str, replacements: Vec<Replacement>) -> OffsetMap<'t> {
+        OffsetMap { original, replacements, last_byte: 0, last_char: 0 }
+    }
+
+    pub fn span_of(&mut self, rewritten: Range<usize>) -> Span {
+        let Range { start, end } = self.original_range(rewritten);
+        let char_start = self.char_offset(start);
+        let char_end = self.char_offset(end);
+        Span { start, end, char_start, char_end }
+    }
+
+    //Byte range in the original text, widened to whole replacements
+    pub fn original_range(&self, rewritten: Range<usize>) -> Range<usize> {
+        let start = match self.replacement_at(rewritten.start) {
+            Some(replacement) => replacement.original.start,
+            None => self.original_offset(rewritten.start),
+        };
+        let end = match self.replacement_at(rewritten.end.saturating_sub(1)) {
+            Some(replacement) if !rewritten.is_empty() => replacement.original.end,
+            _ => self.original_offset(rewritten.end),
+        };
+        start..end
+    }
+
+    fn replacement_at(&self, offset: usize) -> Option<&Replacement> {
+        self.replacements.iter().find(|replacement| replacement.rewritten.contains(&offset))
+    }
+
+    // Offsets outside of replacements are shifted by the replacements before them
+    fn original_offset(&self, offset: usize) -> usize {
+        match self.replacements.iter().rev().find(|replacement| replacement.rewritten.end <= offset) {
+            Some(replacement) => replacement.original.end + (offset - replacement.rewritten.end),
+            None => offset,
+        }
+    }
+
+    fn char_offset(&mut self, byte: usize) -> usize {
+        if byte < self.last_byte {
+            self.last_byte = 0;
+            self.last_char = 0;
+        }
+        self.last_char += self.original[self.last_byte..byte].chars().count();
+        self.last_byte = byte;
+        self.last_char
+    }
+}
@@ -0,0 +1,423 @@
+use std::borrow::Cow;
+use std::collections::hash_map::{self, HashMap};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+use config::VaderConfig;
+use lexicon::{LexiconError, LexiconErrorKind};
+use words::{Word, WordRef};
+
+/**
+ * How a booster word changes the intensity of the words after it. `Incr` and `Decr` use the
+ * `b_incr` and `b_decr` values of the analyzer's `VaderConfig`.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Booster {
+    Incr,
+    Decr,
+    Scalar(f64),
+}
+
+impl Booster {
+    pub fn scalar(&self, config: &VaderConfig) -> f64 {
+        match *self {
+            Booster::Incr => config.b_incr,
+            Booster::Decr => config.b_decr,
+            Booster::Scalar(scalar) => scalar,
+        }
+    }
+}
+
+impl FromStr for Booster {
+    type Err = ();
+
+    fn from_str(raw: &str) -> Result<Booster, ()> {
+        match raw.trim() {
+            "incr" | "INCR" => Ok(Booster::Incr),
+            "decr" | "DECR" => Ok(Booster::Decr),
+            scalar => scalar.parse().map(Booster::Scalar).map_err(|_| ()),
+        }
+    }
+}
+
+/**
+ * Intensity boosters and dampeners like "very" or "kinda".
+ * `BoosterTable::default()` holds the ones from the original implementation.
+ *
+ * Files have one `word<TAB>booster` pair per line, where booster is `incr`, `decr` or a number.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoosterTable {
+    // Words are stored lowercased, so they can also be searched for in lowercased text
+    words: HashMap<Word, Booster>,
+}
+
+impl BoosterTable {
+    pub fn empty() -> BoosterTable {
+        BoosterTable { words: HashMap::new() }
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<BoosterTable, LexiconError> {
+        let mut table = BoosterTable::empty();
+        for_each_line(reader, |line, line_no| {
+            let (word, booster) = split_pair(line, line_no)?;
+            let booster = booster.parse().map_err(|_| LexiconError::new(line_no, word.chars().count() + 2,
+                                                  LexiconErrorKind::InvalidValence(booster.to_string())))?;
+            if table.insert(word, booster).is_some() {
+                return Err(LexiconError::new(line_no, 1, LexiconErrorKind::DuplicateEntry(word.to_string())));
+            }
+            Ok(())
+        })?;
+        Ok(table)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<BoosterTable, LexiconError> {
+        BoosterTable::from_reader(open(path)?)
+    }
+
+    //Adds or replaces a booster, returning the previous one
+    pub fn insert<S: Into<String>>(&mut self, word: S, booster: Booster) -> Option<Booster> {
+        let word = lowercase(Cow::Owned(word.into()));
+        let previous = self.words.remove(WordRef::new(&word));
+        self.words.insert(Word(word), booster);
+        previous
+    }
+
+    pub fn remove(&mut self, word: &str) -> Option<Booster> {
+        self.words.remove(WordRef::new(word))
+    }
+
+    pub fn get(&self, word: &str) -> Option<Booster> {
+        self.words.get(WordRef::new(word)).cloned()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains_key(WordRef::new(word))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn iter(&self) -> BoosterIter<'_> {
+        BoosterIter { inner: self.words.iter() }
+    }
+}
+
+impl Default for BoosterTable {
+    fn default() -> BoosterTable {
+        let mut table = BoosterTable::empty();
+        for (word, booster) in ::BOOSTER_DICT.iter() {
+            table.words.insert(Word(Cow::Borrowed(**word)), *booster);
+        }
+        table
+    }
+}
+
+/**
+ * Iterates over the words of a `BoosterTable`, in no particular order
+ **/
+pub struct BoosterIter<'a> {
+    inner: hash_map::Iter<'a, Word, Booster>,
+}
+
+impl<'a> Iterator for BoosterIter<'a> {
+    type Item = (&'a str, Booster);
+
+    fn next(&mut self) -> Option<(&'a str, Booster)> {
+        self.inner.next().map(|(word, booster)| (word.0.as_ref(), *booster))
+    }
+}
+
+/**
+ * Words that negate the sentiment of the words after them, like "not" or "never". Any word
+ * containing "n't" is treated as a negation too.
+ * `NegationTable::default()` holds the ones from the original implementation.
+ *
+ * Files have one word per line.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegationTable {
+    words: WordSet,
+}
+
+impl NegationTable {
+    pub fn empty() -> NegationTable {
+        NegationTable { words: WordSet::default() }
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<NegationTable, LexiconError> {
+        WordSet::from_reader(reader).map(|words| NegationTable { words })
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<NegationTable, LexiconError> {
+        NegationTable::from_reader(open(path)?)
+    }
+
+    //Adds a negation, returning false if it was already there
+    pub fn insert<S: Into<String>>(&mut self, word: S) -> bool {
+        self.words.insert(Cow::Owned(word.into()))
+    }
+
+    pub fn remove(&mut self, word: &str) -> bool {
+        self.words.remove(word)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    //Checks if a token negates the words after it
+    pub fn is_negation(&self, token: &str) -> bool {
+        self.contains(token) || token.contains("n't")
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.words.iter()
+    }
+}
+
+impl Default for NegationTable {
+    fn default() -> NegationTable {
+        let mut table = NegationTable::empty();
+        for word in ::NEGATION_TOKENS.iter() {
+            table.words.insert(Cow::Borrowed(**word));
+        }
+        table
+    }
+}
+
+/**
+ * Conjunctions like "but" that shift the emphasis of a sentence to the words after them. The
+ * first one in the text scales the valences before it by `but_before_weight` and the ones after
+ * it by `but_after_weight`. `ContrastTable::default()` holds "but".
+ *
+ * Files have one word per line.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastTable {
+    words: WordSet,
+}
+
+impl ContrastTable {
+    pub fn empty() -> ContrastTable {
+        ContrastTable { words: WordSet::default() }
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<ContrastTable, LexiconError> {
+        WordSet::from_reader(reader).map(|words| ContrastTable { words })
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<ContrastTable, LexiconError> {
+        ContrastTable::from_reader(open(path)?)
+    }
+
+    //Adds a conjunction, returning false if it was already there
+    pub fn insert<S: Into<String>>(&mut self, word: S) -> bool {
+        self.words.insert(Cow::Owned(word.into()))
+    }
+
+    pub fn remove(&mut self, word: &str) -> bool {
+        self.words.remove(word)
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.words.iter()
+    }
+}
+
+impl Default for ContrastTable {
+    fn default() -> ContrastTable {
+        let mut table = ContrastTable::empty();
+        table.words.insert(Cow::Borrowed("but"));
+        table
+    }
+}
+
+// A case-insensitive set of words, read from files with one word per line
+#[derive(Debug, Clone, Default, PartialEq)]
+struct WordSet {
+    words: HashSet<Word>,
+}
+
+impl WordSet {
+    fn from_reader<R: Read>(reader: R) -> Result<WordSet, LexiconError> {
+        let mut set = WordSet::default();
+        for_each_line(reader, |line, line_no| {
+            let word = line.trim();
+            if word.contains('\t') {
+                let columns = word.split('\t').count();
+                return Err(LexiconError::new(line_no, 1, LexiconErrorKind::UnexpectedColumns(columns)));
+            }
+            if !set.insert(Cow::Owned(word.to_string())) {
+                return Err(LexiconError::new(line_no, 1, LexiconErrorKind::DuplicateEntry(word.to_string())));
+            }
+            Ok(())
+        })?;
+        Ok(set)
+    }
+
+    fn insert(&mut self, word: Cow<'static, str>) -> bool {
+        self.words.insert(Word(word))
+    }
+
+    fn remove(&mut self, word: &str) -> bool {
+        self.words.remove(WordRef::new(word))
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.words.contains(WordRef::new(word))
+    }
+
+    fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(|word| word.0.as_ref())
+    }
+}
+
+/**
+ * Idioms that contain lexicon words but mean something else, like "the bomb" or "bad ass".
+ * When one appears around a word, its valence replaces the word's valence.
+ * `SpecialIdiomTable::default()` holds the ones from the original implementation.
+ *
+ * Files have one `phrase<TAB>valence` pair per line.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecialIdiomTable {
+    // Lowercased phrases in the order they are checked
+    idioms: Vec<(Cow<'static, str>, f64)>,
+}
+
+impl SpecialIdiomTable {
+    pub fn empty() -> SpecialIdiomTable {
+        SpecialIdiomTable { idioms: Vec::new() }
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<SpecialIdiomTable, LexiconError> {
+        let mut table = SpecialIdiomTable::empty();
+        for_each_line(reader, |line, line_no| {
+            let (phrase, valence) = split_pair(line, line_no)?;
+            let valence = parse_valence(valence, line_no, phrase.chars().count() + 2)?;
+            if table.insert(phrase, valence).is_some() {
+                return Err(LexiconError::new(line_no, 1, LexiconErrorKind::DuplicateEntry(phrase.to_string())));
+            }
+            Ok(())
+        })?;
+        Ok(table)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<SpecialIdiomTable, LexiconError> {
+        SpecialIdiomTable::from_reader(open(path)?)
+    }
+
+    //Adds or replaces an idiom, returning its previous valence
+    pub fn insert<S: Into<String>>(&mut self, phrase: S, valence: f64) -> Option<f64> {
+        let phrase = lowercase(Cow::Owned(phrase.into()));
+        let previous = self.remove(&phrase);
+        self.idioms.push((phrase, valence));
+        previous
+    }
+
+    pub fn remove(&mut self, phrase: &str) -> Option<f64> {
+        let phrase = phrase.to_lowercase();
+        let position = self.idioms.iter().position(|(p, _)| *p == phrase);
+        position.map(|i| self.idioms.remove(i).1)
+    }
+
+    pub fn get(&self, phrase: &str) -> Option<f64> {
+        let phrase = phrase.to_lowercase();
+        self.idioms.iter().find(|&(p, _)| *p == phrase).map(|&(_, valence)| valence)
+    }
+
+    pub fn len(&self) -> usize {
+        self.idioms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.idioms.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.idioms.iter().map(|(phrase, valence)| (phrase.as_ref(), *valence))
+    }
+}
+
+impl Default for SpecialIdiomTable {
+    fn default() -> SpecialIdiomTable {
+        let idioms = ::SPECIAL_CASE_IDIOMS.iter().map(|&(phrase, valence)| (Cow::Borrowed(phrase), valence));
+        SpecialIdiomTable { idioms: idioms.collect() }
+    }
+}
+
+fn lowercase(word: Cow<'static, str>) -> Cow<'static, str> {
+    if word.chars().any(char::is_uppercase) {
+        Cow::Owned(word.to_lowercase())
+    } else {
+        word
+    }
+}
+
+fn open<P: AsRef<Path>>(path: P) -> Result<File, LexiconError> {
+    File::open(path).map_err(|e| LexiconError::new(0, 0, LexiconErrorKind::Io(e)))
+}
+
+// Calls `f` with every non-blank line and its 1-based line number
+pub(crate) fn for_each_line<R, F>(reader: R, mut f: F) -> Result<(), LexiconError>
+    where R: Read, F: FnMut(&str, usize) -> Result<(), LexiconError> {
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|e| LexiconError::new(i + 1, 1, LexiconErrorKind::Io(e)))?;
+        let line = line.trim_end_matches('\r');
+        if !line.trim().is_empty() {
+            f(line, i + 1)?;
+        }
+    }
+    Ok(())
+}
+
+// Splits a "key<TAB>value" line
+pub(crate) fn split_pair(line: &str, line_no: usize) -> Result<(&str, &str), LexiconError> {
+    let columns: Vec<&str> = line.split('\t').collect();
+    match columns.len() {
+        1 => Err(LexiconError::new(line_no, line.chars().count() + 1, LexiconErrorKind::MissingTab)),
+        2 => Ok((columns[0], columns[1])),
+        count => Err(LexiconError::new(line_no, 1, LexiconErrorKind::UnexpectedColumns(count))),
+    }
+}
+
+pub(crate) fn parse_valence(raw: &str, line_no: usize, column: usize) -> Result<f64, LexiconError> {
+    raw.trim().parse().map_err(|_| LexiconError::new(line_no, column, LexiconErrorKind::InvalidValence(raw.to_string())))
+}
@@ -1,7 +1,13 @@
 extern crate unicase;
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::thread;
 use unicase::UniCase;
 
+use Tokenizer;
+
 #[test]
 fn test_lexicon() {
     assert_eq!(*::LEXICON.get(&UniCase::new("feudally")).unwrap(), -0.6);
@@ -18,9 +24,10 @@ fn test_emoji_lexicon() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_parsed_text() {
     let messy_text = "WOAH!!! ,Who? DO u Think you're?? :) :D :^(";
-    let parsed_messy = ::ParsedText::from_text(messy_text);
+    let parsed_messy = ::ParsedText::from_text(messy_text, &::VaderConfig::default());
     let expected_text: Vec<UniCase<&str>> = ["WOAH", "Who", "DO", "Think", "you\'re", ":)", ":D", ":^("].iter().map(| r| UniCase::new(*r)).collect();
     assert_eq!(parsed_messy.tokens, expected_text);
     assert_eq!(parsed_messy.has_mixed_caps, true);
@@ -32,11 +39,64 @@ fn test_parsed_text() {
     assert_eq!(::ParsedText::has_mixed_caps(&::ParsedText::tokenize("Hmm, yeah ME NEITHER")), true);
 }
 
+#[test]
+fn unicode_punctuation_test() {
+    let config = ::VaderConfig::default();
+    let tokens = |text| -> Vec<String> {
+        ::ParsedText::from_text(text, &config).tokens.iter().map(|t| t.to_string()).collect()
+    };
+    assert_eq!(tokens("«¡Genial!» dijo… ¿verdad?"), vec!["Genial", "dijo", "verdad"]);
+    assert_eq!(tokens("„großartig“, c’était “génial”"), vec!["großartig", "c’était", "génial"]);
+    assert_eq!(tokens("「最高」 です。 ＄１００＋"), vec!["最高", "です", "１００"]);
+    // Emoticons are still kept whole
+    assert_eq!(tokens("¡:)! ^_^"), vec!["¡:)!", "^_^"]);
+    // A lone punctuation char is dropped like a lone '-', however many bytes it takes
+    assert_eq!(tokens("good … — ¿ 。 -"), vec!["good"]);
+
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(analyzer.polarity_scores("good …"), analyzer.polarity_scores("good -"));
+    assert_eq!(analyzer.polarity_scores("“great” «hope»"), analyzer.polarity_scores("great hope"));
+
+    // Accented capitals are shouting, in precomposed or decomposed form
+    for caps in &["ÉNORME", "GROẞARTIG", "ÜBERRAGEND", "E\u{301}NORME", "ÇA", "ΥΠΕΡΟΧΟ", "ОТЛИЧНО"] {
+        assert!(::is_all_caps(caps), "{}", caps);
+    }
+    for not_caps in &["Énorme", "GROßartig", "É", "élevé"] {
+        assert!(!::is_all_caps(not_caps), "{}", not_caps);
+    }
+    assert!(::ParsedText::from_text("c'est ÉNORME", &config).has_mixed_caps);
+    let lexicon = hashmap!{ UniCase::new("énorme") => 2.0 };
+    let french = ::SentimentIntensityAnalyzer::from_lexicon(&lexicon);
+    let explanation = french.polarity_scores_explained("c'est ÉNORME");
+    assert_eq!(explanation.tokens[1].valence, 2.0 + 0.733);
+
+    // Fullwidth and doubled marks count as '!' and '?'
+    let emphasis = |text| ::ParsedText::get_punctuation_emphasis(text, &config);
+    assert_eq!(emphasis("すごい！！"), emphasis("wow!!"));
+    assert_eq!(emphasis("wow‼"), emphasis("wow!!"));
+    assert_eq!(emphasis("what⁉ really？？"), emphasis("what!? really??"));
+    assert_eq!(emphasis("¡¿qué?!"), emphasis("qué?!"));
+    // Punctuation emoji are emphasis rather than described like other emoji
+    assert_eq!(analyzer.polarity_scores("great‼"), analyzer.polarity_scores("great!!"));
+    assert_eq!(analyzer.polarity_scores("great‼\u{FE0F} really❓❓"), analyzer.polarity_scores("great!! really??"));
+    let explanation = analyzer.polarity_scores_explained("so ‼\u{FE0F} good❗");
+    // A lone "‼" is dropped like other one char punctuation
+    assert_eq!(explanation.scores, analyzer.polarity_scores("so good!!!"));
+    assert_eq!(explanation.tokens[1].span.range(), 10..14);
+    assert_eq!(explanation.punctuation_amplifier, 3.0 * 0.292);
+
+    // The Python package only counts and strips ASCII punctuation
+    let python_config = ::VaderConfig { compat: ::Compat::Python, ..::VaderConfig::default() };
+    assert_eq!(::ParsedText::get_punctuation_emphasis("wow！！", &python_config), 0.0);
+    let parsed = ::ParsedText::from_text("«great» “hope”", &python_config);
+    assert_eq!(parsed.tokens, vec![UniCase::new("«great»"), UniCase::new("“hope”")]);
+}
+
 #[test]
 fn but_check_test() {
     let tokens: Vec<UniCase<&str>> = ["yeah", "waffles", "are", "great", "but", "have", "you", "ever", "tried", "spam"].iter().map(| r| UniCase::new(*r)).collect();
     let mut sents  = vec![ 0.5,    0.1,       0.0,   0.2,     0.6,   0.25,    0.5,   0.5,    0.5,     0.5];
-    ::but_check(&tokens, &mut sents);
+    ::but_check(&tokens, &mut sents, &::ContrastTable::default(), &::VaderConfig::default());
     assert_eq!(sents, vec![0.25,   0.05,      0.0,   0.1,     0.6,   0.375,  0.75,   0.75,  0.75,   0.75]);
 }
 
@@ -55,3 +115,1026 @@ fn embedded_emoji_test() {
     assert_eq!(analyzer.append_emoji_descriptions(embedded_emoji), "heyyyy grinning face what're you up to???");
     assert_eq!(analyzer.append_emoji_descriptions(multiple_emoji), "woah there grinning face grinning face grinning face :) :)");
 }
+
+#[test]
+fn multi_codepoint_emoji_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(analyzer.append_emoji_descriptions("graduated 👨🏿‍🎓!"), "graduated man student: dark skin tone!");
+    assert_eq!(analyzer.append_emoji_descriptions("live long 🖖🏻"), "live long vulcan salute: light skin tone");
+    assert_eq!(analyzer.append_emoji_descriptions("👨‍👩‍👧"), "family: man, woman, girl");
+    assert_eq!(analyzer.append_emoji_descriptions("🇺🇸🇫🇷"), "United States France");
+    assert_eq!(analyzer.append_emoji_descriptions("1️⃣ ❤️"), "keycap: 1 red heart");
+    // Modifiers of an emoji the lexicon doesn't know are dropped rather than described
+    assert_eq!(analyzer.append_emoji_descriptions("🙂🏽"), "slightly smiling face");
+    assert_eq!(analyzer.append_emoji_descriptions("🏻"), "light skin tone");
+    assert_eq!(analyzer.append_emoji_descriptions("café"), "café");
+    assert_eq!(analyzer.polarity_scores("I love it ❤️").compound, analyzer.polarity_scores("I love it ❤").compound);
+}
+
+#[test]
+fn sentiment_scores_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let scores = analyzer.polarity_scores("VADER is smart, handsome, and funny.");
+    assert_eq!(scores.compound, 0.8316320352807864);
+    assert_eq!(scores.label(), ::SentimentLabel::Positive);
+    assert_eq!(analyzer.polarity_scores("Today SUX!").label(), ::SentimentLabel::Negative);
+    assert_eq!(analyzer.polarity_scores("The book was a book.").label(), ::SentimentLabel::Neutral);
+    assert_eq!(scores.label_with(&::LabelThresholds::new(0.9, -0.9)), ::SentimentLabel::Neutral);
+
+    let map: HashMap<&str, f64> = scores.into();
+    assert_eq!(map["compound"], scores.compound);
+    assert_eq!(map["neu"], scores.neu);
+    assert_eq!(::SentimentScores::try_from(&map), Ok(scores));
+
+    let mut partial = map.clone();
+    partial.remove("pos");
+    assert_eq!(::SentimentScores::try_from(&partial), Err(::MissingScoreError { key: "pos" }));
+}
+
+#[test]
+fn explanation_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let text = "The plot was not very GOOD, but the acting is great!";
+    let explanation = analyzer.polarity_scores_explained(text);
+    assert_eq!(explanation.scores, analyzer.polarity_scores(text));
+    assert_eq!(explanation.punctuation_amplifier, 0.292);
+
+    let tokens: Vec<&str> = explanation.tokens.iter().map(|t| t.token.as_str()).collect();
+    assert_eq!(tokens, vec!["The", "plot", "was", "not", "very", "GOOD", "but", "the", "acting", "is", "great"]);
+
+    let very = &explanation.tokens[4];
+    assert_eq!(very.base_valence, None);
+    assert!(very.adjustments.is_empty());
+
+    let good = &explanation.tokens[5];
+    assert_eq!(good.base_valence, Some(1.9));
+    let rules: Vec<::Rule> = good.adjustments.iter().map(|a| a.rule).collect();
+    // "very" is picked up both as the preceding booster and inside the special idiom window
+    assert_eq!(rules, vec![::Rule::CapsEmphasis, ::Rule::Booster, ::Rule::Negation, ::Rule::Booster, ::Rule::ButContrast]);
+    assert_eq!(good.adjustments[1].trigger, Some(4));
+    assert_eq!(good.adjustments[2].trigger, Some(3));
+    assert_eq!(good.adjustments[4].trigger, Some(6));
+    for pair in good.adjustments.windows(2) {
+        assert_eq!(pair[0].after, pair[1].before);
+    }
+    assert_eq!(good.adjustments.last().unwrap().after, good.valence);
+
+    let great = &explanation.tokens[10];
+    assert_eq!(great.valence, great.base_valence.unwrap() * 1.5);
+
+    let total: f64 = explanation.tokens.iter().map(|t| t.valence).sum();
+    assert_eq!(explanation.normalization.raw_total, total);
+    assert_eq!(explanation.normalization.amplified_total, total + 0.292);
+    assert_eq!(explanation.normalization.compound, explanation.scores.compound);
+}
+
+#[test]
+fn config_test() {
+    let default = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(*default.config(), ::VaderConfig::default());
+    let text = "The food was very good but the service was slow!!";
+    let scores = default.polarity_scores(text);
+    let rebuilt = ::SentimentIntensityAnalyzer::builder().config(::VaderConfig::default()).build();
+    assert_eq!(rebuilt.polarity_scores(text), scores);
+
+    let config = ::VaderConfig {
+        b_incr: 0.0,
+        but_before_weight: 1.0,
+        but_after_weight: 1.0,
+        emark_incr: 0.0,
+        ..::VaderConfig::default()
+    };
+    let plain = ::SentimentIntensityAnalyzer::builder().config(config).build();
+    let good = plain.polarity_scores("The food was good").compound;
+    assert_eq!(plain.polarity_scores("The food was very good!!").compound, good);
+    assert!(plain.polarity_scores(text).compound > scores.compound);
+
+    let alpha = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { normalization_alpha: 1.0, ..::VaderConfig::default() })
+        .build();
+    assert!(alpha.polarity_scores("The food was good").compound > good);
+}
+
+#[test]
+fn elongation_test() {
+    assert_eq!(::elongation::candidates("sooooo"), vec!["soo", "so"]);
+    assert_eq!(::elongation::candidates("GOOOOD")[0], "GOOD");
+    assert_eq!(::elongation::candidates("loooovvvve"), vec!["loovve", "lovve", "loove", "love"]);
+    assert_eq!(::elongation::candidates("hahahaha"), vec!["hahaha", "haha", "hah", "ha"]);
+    assert!(::elongation::candidates("good").is_empty());
+    assert!(::elongation::candidates("!!!!").is_empty());
+
+    let config = ::VaderConfig { normalize_elongation: true, ..::VaderConfig::default() };
+    let analyzer = ::SentimentIntensityAnalyzer::builder().config(config.clone()).build();
+    let default = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(default.polarity_scores("The food was goooood").compound, 0.0);
+
+    let explanation = analyzer.polarity_scores_explained("The food was goooood!!");
+    let good = &explanation.tokens[3];
+    assert_eq!((good.token.as_str(), good.base_valence, good.valence), ("good", Some(1.9), 1.9 + 0.293));
+    assert_eq!(good.adjustments.iter().map(|a| a.rule).collect::<Vec<_>>(), vec![::Rule::Elongation]);
+    assert_eq!(good.span.range(), 13..20);
+    assert_eq!(explanation.punctuation_amplifier, analyzer.polarity_scores_explained("The food was good!!").punctuation_amplifier);
+    assert_eq!(explanation.scores, analyzer.polarity_scores("The food was goooood!!"));
+
+    // An elongated booster adds its emphasis to the word after it, like one in caps
+    let so = analyzer.polarity_scores_explained("sooooo good");
+    assert_eq!(so.tokens[1].valence, 1.9 + (0.293 + 0.293));
+    assert_eq!(so.tokens[0].span.range(), 0..6);
+    assert!(analyzer.polarity_scores("nooooot good").compound < 0.0);
+    assert!(analyzer.polarity_scores("hahahaha").compound > 0.0);
+    assert_eq!(analyzer.polarity_scores("GOOOOOD"), analyzer.polarity_scores("goooood"));
+
+    // Words that are known as they are, or have no known spelling, are left alone
+    assert_eq!(analyzer.polarity_scores("hahaha"), default.polarity_scores("hahaha"));
+    assert_eq!(analyzer.polarity_scores("zzzzz 😀 hmmm"), default.polarity_scores("zzzzz 😀 hmmm"));
+
+    let plain = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { elongation_incr: 0.0, ..config })
+        .build();
+    assert_eq!(plain.polarity_scores("sooo goooood"), default.polarity_scores("so good"));
+    let texts = ["sooo goooood", "hmm", "baaad 😀 loooove"];
+    let batch: Vec<::SentimentScores> = texts.iter().map(|t| analyzer.polarity_scores(t)).collect();
+    assert_eq!(analyzer.polarity_scores_batch(&texts), batch);
+}
+
+#[test]
+fn social_media_test() {
+    let config = ::VaderConfig { social_media: true, ..::VaderConfig::default() };
+    let analyzer = ::SentimentIntensityAnalyzer::builder().config(config.clone()).build();
+    let default = ::SentimentIntensityAnalyzer::new();
+    let tokens = |text| -> Vec<String> {
+        analyzer.polarity_scores_explained(text).tokens.iter().map(|t| t.token.clone()).collect()
+    };
+
+    assert_eq!(tokens("#blessed @brand http://x.co/bad!"), vec!["blessed", "@brand", "http://x.co/bad"]);
+    assert_eq!(tokens("#NotHappy #not_happy #nothappy"), vec!["Not", "Happy", "not", "happy", "not", "happy"]);
+    assert_eq!(tokens("#NYCRocks #2020 #summer2day"), vec!["NYC", "Rocks", "2020", "summer", "2", "day"]);
+    assert_eq!(tokens("mail me at love@example.com, www.great.org."), vec!["mail", "me", "at", "love@example.com", "www.great.org"]);
+    // Hashtags that can't be split into known words stay whole
+    assert_eq!(tokens("#throwbackthursday #happy"), vec!["throwbackthursday", "happy"]);
+    // Emoticons and other punctuation are tokenized as usual
+    assert_eq!(tokens("@_@ :) #1 ok!!"), vec!["@_@", ":)", "#1", "ok"]);
+    // Punctuation before a mention, hashtag or URL is trimmed too
+    assert_eq!(tokens("(@love) (#NotHappy) \"#blessed\" <https://x.co/bad>"),
+               vec!["@love", "Not", "Happy", "blessed", "https://x.co/bad"]);
+
+    // Mentions, URLs and emails are neutral, even when they contain lexicon words
+    assert!(default.polarity_scores("@love").compound > 0.0);
+    for text in &["@love", "https://example.com/awful", "great@example.com", "@not good"] {
+        let explanation = analyzer.polarity_scores_explained(text);
+        assert_eq!(explanation.tokens[0].valence, 0.0, "{}", text);
+    }
+    assert_eq!(analyzer.polarity_scores("@not good"), default.polarity_scores("someone good"));
+
+    // Hashtag words are scored with the usual rules
+    assert_eq!(analyzer.polarity_scores("#NotHappy"), default.polarity_scores("not happy"));
+    assert_eq!(analyzer.polarity_scores("(#NotHappy)"), default.polarity_scores("not happy"));
+    assert_eq!(analyzer.polarity_scores("(@love)").compound, 0.0);
+    assert_eq!(analyzer.polarity_scores("#sohappy"), default.polarity_scores("so happy"));
+    let explanation = analyzer.polarity_scores_explained("so #NotHappy");
+    assert_eq!(explanation.tokens[2].span.range(), 7..12);
+    assert_eq!(explanation.tokens[2].adjustments[0].rule, ::Rule::Negation);
+
+    let texts = ["#blessed @brand http://x.co/bad", "#nothappy"];
+    let batch: Vec<::SentimentScores> = texts.iter().map(|t| analyzer.polarity_scores(t)).collect();
+    assert_eq!(analyzer.polarity_scores_batch(&texts), batch);
+    let python = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { compat: ::Compat::Python, ..config })
+        .build();
+    assert_eq!(python.polarity_scores("@love"), analyzer.polarity_scores("@love"));
+}
+
+// Splits on commas as well as whitespace, and reads "gr8" as "great"
+struct TextingTokenizer;
+
+impl ::Tokenizer for TextingTokenizer {
+    fn tokenize<'t>(&self, text: &'t str, tokens: &mut Vec<::Token<'t>>) {
+        for word in text.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()) {
+            let token = ::Token::from_slice(text, word);
+            if word.eq_ignore_ascii_case("gr8") {
+                tokens.push(::Token { text: "great", ..token });
+            } else {
+                tokens.push(token);
+            }
+        }
+    }
+}
+
+#[test]
+fn tokenizer_test() {
+    let mut found = Vec::new();
+    ::DefaultTokenizer.tokenize("Wow, :) so GOOD!! a", &mut found);
+    let texts: Vec<&str> = found.iter().map(|t| t.text).collect();
+    assert_eq!(texts, vec!["Wow", ":)", "so", "GOOD"]);
+    assert_eq!(found[3].span, 11..15);
+    assert_eq!(found.iter().map(|t| t.is_emoticon).collect::<Vec<_>>(), vec![false, true, false, false]);
+
+    // Setting the default tokenizer doesn't change any scores
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let explicit = ::SentimentIntensityAnalyzer::builder().tokenizer(::DefaultTokenizer).build();
+    for text in batch_corpus() {
+        assert_eq!(explicit.polarity_scores(&text), analyzer.polarity_scores(&text), "{}", text);
+    }
+    assert!(analyzer.polarity_scores_explained("so :)").tokens[1].is_emoticon);
+
+    let texting = ::SentimentIntensityAnalyzer::builder().tokenizer(TextingTokenizer).build();
+    assert_eq!(texting.polarity_scores("food,gr8"), analyzer.polarity_scores("food great"));
+    let explanation = texting.polarity_scores_explained("food was GR8 😀");
+    assert_eq!(explanation.tokens[2].token, "great");
+    assert_eq!(explanation.tokens[2].span.range(), 9..12);
+    assert_eq!(explanation.tokens[2].base_valence, Some(3.1));
+    // A custom tokenizer replaces the one the config picks, and still sees emoji descriptions
+    let social = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { social_media: true, ..::VaderConfig::default() })
+        .tokenizer(TextingTokenizer)
+        .build();
+    let tokens: Vec<String> = social.polarity_scores_explained("#happy,😀").tokens.into_iter().map(|t| t.token).collect();
+    assert_eq!(tokens, vec!["#happy", "grinning", "face"]);
+}
+
+#[test]
+fn lexicon_loading_test() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/resources/vader_lexicon.txt");
+    let vader_format = ::Lexicon::from_path_lenient(path).unwrap();
+    assert_eq!(&vader_format, &*::DEFAULT_LEXICON);
+    for (word, valence) in vader_format.iter() {
+        assert_eq!(::LEXICON.get(&UniCase::new(word)), Some(&valence));
+    }
+    // Repeated words take the later line, but in Python mode ":P" doesn't replace ":p"
+    assert_eq!(vader_format.get("lol"), Some(1.8));
+    assert_eq!(vader_format.get(":p"), Some(1.4));
+    let repeated = ":p\t1.0\n:P\t-1.0\n";
+    assert_eq!(::Lexicon::from_reader_lenient(repeated.as_bytes()).unwrap().get(":p"), Some(-1.0));
+    assert_eq!(::Lexicon::from_reader_python(repeated.as_bytes()).unwrap().get(":p"), Some(1.0));
+    let err = ::Lexicon::from_path(path).unwrap_err();
+    assert_eq!(err.line, 124);
+    assert!(matches!(err.kind, ::LexiconErrorKind::DuplicateEntry(ref w) if w == ":-p"));
+
+    let custom: ::Lexicon = "bullish\t2.5\nbearish\t-2.5\r\n\nGood\t1.0\n".parse().unwrap();
+    assert_eq!(custom.len(), 3);
+    assert_eq!(custom.get("BULLISH"), Some(2.5));
+    assert_eq!(custom.get("good"), Some(1.0));
+    assert_eq!(custom.get("great"), None);
+
+    let analyzer = ::SentimentIntensityAnalyzer::from_custom_lexicon(&custom);
+    assert!(analyzer.polarity_scores("Very bullish on this stock").compound > 0.0);
+    assert!(analyzer.polarity_scores("not bullish").compound < 0.0);
+    assert_eq!(analyzer.polarity_scores("what a great day").compound, 0.0);
+
+    let path = ::std::env::temp_dir().join("vader_sentiment_lexicon_loading_test.txt");
+    ::std::fs::write(&path, "happy\t2.7\t0.8\t[3, 3, 2, 3, 3, 2, 3, 3, 2, 3]\nsad\t-2.1\n").unwrap();
+    let from_file = ::Lexicon::from_path(&path).unwrap();
+    ::std::fs::remove_file(&path).unwrap();
+    assert_eq!(from_file.get("happy"), Some(2.7));
+    assert_eq!(from_file.get("sad"), Some(-2.1));
+}
+
+#[test]
+fn lexicon_error_test() {
+    use LexiconErrorKind::*;
+
+    let err = "good\t1.0\nbad -1.0\n".parse::<::Lexicon>().unwrap_err();
+    assert_eq!((err.line, err.column), (2, 9));
+    assert!(matches!(err.kind, MissingTab));
+
+    let err = "good\t1.0\nbåd\tminus one\n".parse::<::Lexicon>().unwrap_err();
+    assert_eq!((err.line, err.column), (2, 5));
+    assert!(matches!(err.kind, InvalidValence(ref v) if v == "minus one"));
+    assert_eq!(err.to_string(), "line 2, column 5: valence \"minus one\" is not a number");
+
+    let repeated = "good\t1.0\nbad\t-1.0\ngood\t1.5\n";
+    let err = repeated.parse::<::Lexicon>().unwrap_err();
+    assert_eq!((err.line, err.column), (3, 1));
+    assert!(matches!(err.kind, DuplicateEntry(ref w) if w == "good"));
+    let err = "good\t1.0\nGOOD\t1.5\n".parse::<::Lexicon>().unwrap_err();
+    assert!(matches!(err.kind, DuplicateEntry(ref w) if w == "GOOD"));
+    assert_eq!("good\t1.0\ngood\t1.0\n".parse::<::Lexicon>().unwrap().len(), 1);
+    assert_eq!(::Lexicon::from_reader_lenient(repeated.as_bytes()).unwrap().get("good"), Some(1.5));
+
+    let err = ::parse_raw_lexicon("good\t1.9\t0.5\t[2, 2]\nbad\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 4));
+    assert!(matches!(err.kind, MissingTab));
+    let err = ::parse_raw_lexicon("good\tgreat\n").unwrap_err();
+    assert!(matches!(err.kind, InvalidValence(ref v) if v == "great"));
+    assert!(matches!(::parse_raw_emoji_lexicon("😀\n").unwrap_err().kind, MissingTab));
+
+    let err = "good\t1.0\t0.5\n".parse::<::Lexicon>().unwrap_err();
+    assert!(matches!(err.kind, UnexpectedColumns(3)));
+
+    let err = ::Lexicon::from_path("/this/path/does/not/exist.txt").unwrap_err();
+    assert!(matches!(err.kind, Io(_)));
+}
+
+#[test]
+fn shared_analyzer_test() {
+    fn assert_shareable<T: Send + Sync + Clone + 'static>(_: &T) {}
+
+    let words = vec![("moon".to_string(), 2.0), ("rekt".to_string(), -3.0)];
+    let lexicon: Arc<::Lexicon> = Arc::new(words.into_iter().collect());
+    let analyzer = ::SentimentIntensityAnalyzer::from_shared_lexicon(lexicon.clone());
+    assert_shareable(&analyzer);
+    assert_shareable(&::SentimentIntensityAnalyzer::new());
+
+    let expected = analyzer.polarity_scores("to the MOON, totally not rekt");
+    let handles: Vec<_> = (0..4).map(|_| {
+        let analyzer = analyzer.clone();
+        thread::spawn(move || analyzer.polarity_scores("to the MOON, totally not rekt"))
+    }).collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+    assert!(expected.compound > 0.0);
+    assert_eq!(Arc::strong_count(&lexicon), 2);
+}
+
+#[test]
+fn lexicon_entry_test() {
+    let entry = ::DEFAULT_LEXICON.entry("$:").unwrap();
+    assert_eq!(entry.mean, -1.5);
+    assert_eq!(entry.std_dev, Some(0.80623));
+    assert_eq!(entry.ratings, vec![-1, -1, -1, -1, -3, -1, -3, -1, -2, -1]);
+    let recomputed = ::LexiconEntry::from_ratings(entry.ratings.clone());
+    assert_eq!(recomputed.mean, entry.mean);
+    assert!((recomputed.std_dev.unwrap() - 0.80623).abs() < 1e-5);
+
+    assert_eq!(::DEFAULT_LEXICON.len(), ::LEXICON.len());
+    for (word, valence) in ::LEXICON.iter() {
+        assert_eq!(::DEFAULT_LEXICON.get(word), Some(*valence));
+    }
+
+    let err = "good\t1.9\t0.9\t[2, 2, x]\n".parse::<::Lexicon>().unwrap_err();
+    assert_eq!(err.column, 14);
+    assert!(matches!(err.kind, ::LexiconErrorKind::InvalidRatings(_)));
+    let err = "good\t1.9\tsome\t[2, 2]\n".parse::<::Lexicon>().unwrap_err();
+    assert_eq!(err.column, 10);
+    assert!(matches!(err.kind, ::LexiconErrorKind::InvalidStdDev(_)));
+}
+
+#[test]
+fn uncertainty_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let text = "The plot was good, but the ending was not great!";
+    let (scores, uncertainty) = analyzer.polarity_scores_with_uncertainty(text);
+    let uncertainty = uncertainty.unwrap();
+    assert_eq!(scores, analyzer.polarity_scores(text));
+    assert!(uncertainty.std_dev > 0.0);
+    assert!(uncertainty.compound_low < scores.compound && scores.compound < uncertainty.compound_high);
+    assert_eq!(uncertainty.unrated_words, 0);
+
+    let (_, neutral) = analyzer.polarity_scores_with_uncertainty("The plot was a plot");
+    assert_eq!(neutral.unwrap().width(), 0.0);
+
+    let legacy = ::SentimentIntensityAnalyzer::from_lexicon(&::LEXICON);
+    assert_eq!(legacy.polarity_scores_with_uncertainty(text), (scores, None));
+
+    let custom: ::Lexicon = vec![("good", 2.0)].into_iter().collect();
+    let analyzer = ::SentimentIntensityAnalyzer::from_custom_lexicon(&custom);
+    let (_, uncertainty) = analyzer.polarity_scores_with_uncertainty("good stuff");
+    assert_eq!(uncertainty.unwrap().unrated_words, 1);
+}
+
+#[test]
+fn laden_idioms_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    assert!(analyzer.polarity_scores("I am feeling under the weather today").compound < 0.0);
+    assert!(analyzer.polarity_scores("Break a leg tonight").compound > 0.0);
+    assert!(analyzer.polarity_scores("The company is in the black").compound > 0.0);
+    assert_eq!(analyzer.polarity_scores("The table is in the kitchen").compound, 0.0);
+
+    let explanation = analyzer.polarity_scores_explained("Sadly we are in the red, but our new hire is on the ball");
+    let found: Vec<(&str, usize, usize)> = explanation.idioms.iter()
+        .map(|m| (m.phrase.as_str(), m.start, m.end)).collect();
+    assert_eq!(found, vec![("in the red", 3, 6), ("on the ball", 11, 14)]);
+    assert_eq!(explanation.tokens[3].adjustments[0].rule, ::Rule::LadenIdiom);
+    assert_eq!(explanation.tokens[11].valence, 2.0 * 1.5);
+
+    let mut idioms = ::IdiomTable::default();
+    assert_eq!(idioms.insert("to the moon", 2.5), None);
+    assert_eq!(idioms.insert("in the red", -3.0), Some(-2.0));
+    assert_eq!(idioms.remove("cut the mustard"), Some(2.0));
+    let custom = ::SentimentIntensityAnalyzer::builder().idioms(idioms).build();
+    assert!(custom.polarity_scores("This stock is going TO THE MOON").compound > 0.0);
+    assert!(custom.polarity_scores("we are in the red").compound < analyzer.polarity_scores("we are in the red").compound);
+    assert!(analyzer.polarity_scores("it does not cut the mustard").compound < 0.0);
+    // without the idiom, "cut" is just a negated negative word
+    assert!(custom.polarity_scores("it does not cut the mustard").compound > 0.0);
+}
+
+#[test]
+fn word_tables_test() {
+    let boosters = ::BoosterTable::default();
+    assert_eq!(boosters.len(), ::BOOSTER_DICT.len());
+    assert_eq!(boosters.get("VERY"), Some(::Booster::Incr));
+    assert_eq!(boosters.get("kinda"), Some(::Booster::Decr));
+    let negations = ::NegationTable::default();
+    assert_eq!(negations.len(), ::NEGATION_TOKENS.len());
+    assert!(negations.is_negation("Never") && negations.is_negation("wouldn't"));
+    let special = ::SpecialIdiomTable::default();
+    let phrases: Vec<(&str, f64)> = special.iter().collect();
+    assert_eq!(phrases, ::SPECIAL_CASE_IDIOMS.to_vec());
+
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let text = "The quarter was good, but not great";
+    let mut custom = analyzer.clone();
+    assert!(custom.negations_mut().remove("not"));
+    assert!(custom.polarity_scores(text).compound > analyzer.polarity_scores(text).compound);
+    assert!(analyzer.negations().contains("not"));
+
+    let mut boosters = ::BoosterTable::default();
+    assert_eq!(boosters.insert("Bullish", ::Booster::Scalar(0.5)), None);
+    assert_eq!(boosters.insert("very", ::Booster::Decr), Some(::Booster::Incr));
+    let custom = ::SentimentIntensityAnalyzer::builder().boosters(boosters).build();
+    assert!(custom.polarity_scores("bullish good news").compound > analyzer.polarity_scores("good news").compound);
+    assert!(custom.polarity_scores("very good news").compound < analyzer.polarity_scores("good news").compound);
+
+    let mut special = ::SpecialIdiomTable::default();
+    assert_eq!(special.remove("Yeah Right"), Some(-2.0));
+    assert_eq!(special.insert("to the moon", 3.0), None);
+    let custom = ::SentimentIntensityAnalyzer::builder().special_idioms(special).build();
+    assert!(analyzer.polarity_scores("it was yeah right, great job").compound < 0.0);
+    assert!(custom.polarity_scores("it was yeah right, great job").compound > 0.0);
+
+    let boosters = ::BoosterTable::from_reader("hugely\tincr\nslightly\t-0.1\n".as_bytes()).unwrap();
+    assert_eq!(boosters.get("slightly"), Some(::Booster::Scalar(-0.1)));
+    let negations = ::NegationTable::from_reader("hardly\n\nbarely\n".as_bytes()).unwrap();
+    assert_eq!(negations.len(), 2);
+    let special = ::SpecialIdiomTable::from_reader("the bomb\t3.0\n".as_bytes()).unwrap();
+    assert_eq!(special.get("THE BOMB"), Some(3.0));
+    let err = ::BoosterTable::from_reader("hugely\tlots\n".as_bytes()).unwrap_err();
+    assert_eq!((err.line, err.column), (1, 8));
+    let err = ::NegationTable::from_reader("not\nnot\n".as_bytes()).unwrap_err();
+    assert!(matches!(err.kind, ::LexiconErrorKind::DuplicateEntry(_)));
+}
+
+#[test]
+fn split_sentences_test() {
+    let split = |text| ::split_sentences(text).iter().map(|s| s.text).collect::<Vec<&str>>();
+    assert_eq!(split("The food was great. The service was slow!  Would I go back? Maybe..."),
+               vec!["The food was great.", "The service was slow!", "Would I go back?", "Maybe..."]);
+    assert_eq!(split("Dr. Smith met Mr. J. R. Jones in the U.S. on Jan. 5, e.g. at noon. Nice."),
+               vec!["Dr. Smith met Mr. J. R. Jones in the U.S. on Jan. 5, e.g. at noon.", "Nice."]);
+    assert_eq!(split("I waited... and waited... Then it came."), vec!["I waited... and waited...", "Then it came."]);
+    assert_eq!(split("Loved it :) Best movie ever o.O wow"), vec!["Loved it :)", "Best movie ever o.O wow"]);
+    assert_eq!(split("He said \"stop!\" She left (finally.) Done"), vec!["He said \"stop!\"", "She left (finally.)", "Done"]);
+    assert_eq!(split("first line\n\nsecond line\nstill second"), vec!["first line", "second line\nstill second"]);
+    assert_eq!(split("Version 2.5 is out!Really"), vec!["Version 2.5 is out!Really"]);
+    assert!(split(" \n ").is_empty());
+    // Abbreviations that are also words only count before a number
+    assert_eq!(split("The answer is no. This is great."), vec!["The answer is no.", "This is great."]);
+    assert_eq!(split("We met on Sat. It was awful."), vec!["We met on Sat.", "It was awful."]);
+    assert_eq!(split("See Fig. 3 and No. 5 on Sat. 12 Mar. Then leave."),
+               vec!["See Fig. 3 and No. 5 on Sat. 12 Mar.", "Then leave."]);
+
+    let text = "  Hi there. Bye!";
+    let sentences = ::split_sentences(text);
+    assert_eq!((sentences[1].start, sentences[1].end), (12, 16));
+    assert_eq!(&text[sentences[0].start..sentences[0].end], "Hi there.");
+}
+
+#[test]
+fn document_scores_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let text = "The room was lovely and the staff were great. The view was nice. But the food was awful!";
+    let document = analyzer.document_scores(text);
+    assert_eq!(document.sentences.len(), 3);
+    // The "but" in the last sentence no longer dampens the first two
+    assert_eq!(document.sentences[0].scores, analyzer.polarity_scores("The room was lovely and the staff were great."));
+    assert_eq!(analyzer.polarity_scores_explained(text).tokens[3].adjustments[0].rule, ::Rule::ButContrast);
+    assert_eq!(document.max, Some(0));
+    assert_eq!(document.min_sentence().unwrap().text, "But the food was awful!");
+    let compounds: Vec<f64> = document.sentences.iter().map(|s| s.scores.compound).collect();
+    assert!((document.mean_compound - compounds.iter().sum::<f64>() / 3.0).abs() < 1e-12);
+    let tokens: Vec<usize> = document.sentences.iter().map(|s| s.tokens).collect();
+    assert_eq!(tokens, vec![9, 4, 5]);
+    let weighted = (compounds[0] * 9.0 + compounds[1] * 4.0 + compounds[2] * 5.0) / 18.0;
+    assert!((document.weighted_compound - weighted).abs() < 1e-12);
+
+    let empty = analyzer.document_scores("   ");
+    assert_eq!((empty.mean_compound, empty.weighted_compound, empty.max, empty.min), (0.0, 0.0, None, None));
+}
+
+#[test]
+fn token_span_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let text = "Café was GREAT, not bad 😀👍🏽!";
+    let explanation = analyzer.polarity_scores_explained(text);
+    let tokens: Vec<(&str, &str)> = explanation.tokens.iter()
+        .map(|t| (t.token.as_str(), &text[t.span.range()])).collect();
+    assert_eq!(tokens, vec![("Café", "Café"), ("was", "was"), ("GREAT", "GREAT"), ("not", "not"), ("bad", "bad"),
+                            ("grinning", "😀"), ("face", "😀"), ("thumbs", "👍🏽"), ("up", "👍🏽"),
+                            ("medium", "👍🏽"), ("skin", "👍🏽"), ("tone", "👍🏽")]);
+    let great = explanation.tokens[2].span;
+    assert_eq!((great.start, great.end, great.char_start, great.char_end), (10, 15, 9, 14));
+    let thumbs = explanation.tokens[7].span;
+    assert_eq!((thumbs.char_start, thumbs.char_end), (25, 27));
+    let chars: Vec<char> = text.chars().collect();
+    assert_eq!(chars[thumbs.char_start..thumbs.char_end].iter().collect::<String>(), "👍🏽");
+
+    // A word glued to an emoji covers both
+    let text = "sooo😀good";
+    let explanation = analyzer.polarity_scores_explained(text);
+    let spans: Vec<&str> = explanation.tokens.iter().map(|t| &text[t.span.range()]).collect();
+    assert_eq!(spans, vec!["sooo", "😀", "😀good"]);
+
+    // A joiner after a combining mark is dropped on its own, not as part of the emoji before it
+    let text = "🏽\u{301}\u{200d}";
+    let explanation = analyzer.polarity_scores_explained(text);
+    let spans: Vec<&str> = explanation.tokens.iter().map(|t| &text[t.span.range()]).collect();
+    assert_eq!(spans, vec!["🏽", "🏽", "🏽\u{301}\u{200d}"]);
+    let text = "👍\u{301}\u{200d}good";
+    let explanation = analyzer.polarity_scores_explained(text);
+    assert_eq!(&text[explanation.tokens.last().unwrap().span.range()], text);
+}
+
+// Doubles words in a domain where they carry more weight
+struct Doubled(&'static str);
+
+impl ::ValenceRule for Doubled {
+    fn name(&self) -> &str {
+        "doubled"
+    }
+
+    fn stage(&self) -> ::Stage {
+        ::Stage::Word
+    }
+
+    fn adjust_word(&self, context: &::RuleContext, word: &mut ::WordValence) {
+        if context.token(word.index()).eq_ignore_ascii_case(self.0) {
+            let doubled = word.valence() * 2.0;
+            word.set(::Rule::Custom, None, doubled);
+        }
+    }
+}
+
+// "allegedly" before a word halves it
+struct Allegedly;
+
+impl ::ValenceRule for Allegedly {
+    fn name(&self) -> &str {
+        "allegedly"
+    }
+
+    fn stage(&self) -> ::Stage {
+        ::Stage::Preceding
+    }
+
+    fn adjust_word(&self, context: &::RuleContext, word: &mut ::WordValence) {
+        let before = word.index() - word.distance();
+        if context.token(before).eq_ignore_ascii_case("allegedly") {
+            let halved = word.valence() / 2.0;
+            word.set(::Rule::Custom, Some(before), halved);
+        }
+    }
+}
+
+#[test]
+fn rule_pipeline_test() {
+    let default = ::RulePipeline::default();
+    let names: Vec<&str> = default.names().collect();
+    assert_eq!(names, vec!["no", "no_negation", "caps_emphasis", "elongation", "booster", "never_intensifier",
+                           "negation", "special_idioms", "least", "laden_idioms", "but_contrast", "punctuation"]);
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let explicit = ::SentimentIntensityAnalyzer::builder().rules(default.clone()).build();
+    for text in batch_corpus() {
+        assert_eq!(explicit.polarity_scores(&text), analyzer.polarity_scores(&text), "{}", text);
+    }
+
+    // Disabling rules
+    let mut rules = default.clone();
+    assert!(rules.remove("negation"));
+    assert!(!rules.remove("negation"));
+    assert!(rules.remove("punctuation"));
+    let unnegated = ::SentimentIntensityAnalyzer::builder().rules(rules).build();
+    assert_eq!(unnegated.polarity_scores("not good!!").compound, analyzer.polarity_scores("good").compound);
+    let empty = ::SentimentIntensityAnalyzer::builder().rules(::RulePipeline::empty()).build();
+    assert_eq!(empty.polarity_scores("NOT very GOOD, but fine!!!").compound, analyzer.polarity_scores("good fine").compound);
+
+    // Reordering: with "least" before caps emphasis, "least GOOD" is emphasized after being negated
+    let mut rules = default.clone();
+    rules.remove("least");
+    rules.insert(rules.position("caps_emphasis").unwrap(), ::rules::LeastNegation);
+    let reordered = ::SentimentIntensityAnalyzer::builder().rules(rules).build();
+    let valence = |analyzer: &::SentimentIntensityAnalyzer, text| analyzer.polarity_scores_explained(text).tokens[2].valence;
+    assert_eq!(valence(&analyzer, "the least GOOD"), (1.9 + 0.733) * -0.74);
+    assert_eq!(valence(&reordered, "the least GOOD"), 1.9 * -0.74 - 0.733);
+
+    // Custom rules are recorded in explanations and run at their place in the pipeline
+    let mut domain = analyzer.clone();
+    domain.rules_mut().insert(0, Doubled("profit"));
+    let position = domain.rules().position("booster").unwrap();
+    domain.rules_mut().insert(position, Allegedly);
+    assert_eq!(analyzer.rules().len(), 12);
+    assert_eq!(valence(&domain, "is very profit"), 1.9 * 2.0 + 0.293);
+    assert_eq!(valence(&domain, "is allegedly profit"), 1.9 * 2.0 / 2.0);
+    assert_eq!(valence(&domain, "allegedly very profit"), (1.9 * 2.0 + 0.293) / 2.0);
+    let explanation = domain.polarity_scores_explained("not profit");
+    let rules: Vec<::Rule> = explanation.tokens[1].adjustments.iter().map(|a| a.rule).collect();
+    assert_eq!(rules, vec![::Rule::Custom, ::Rule::Negation]);
+    assert_eq!(domain.polarity_scores("not profit").compound, explanation.scores.compound);
+}
+
+#[test]
+fn language_test() {
+    assert_eq!(::Language::from_code("pt-BR"), Some(::Language::Portuguese));
+    assert_eq!(::Language::from_code("DE"), Some(::Language::German));
+    assert_eq!(::Language::from_code("nl"), None);
+    assert_eq!(::Language::Spanish.code(), "es");
+    assert_eq!(::Language::Spanish.is_available(), cfg!(feature = "spanish"));
+
+    let english = ::SentimentIntensityAnalyzer::for_language(::Language::English).unwrap();
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(english.language(), ::Language::English);
+    let text = "The food was good, but the service was NOT great!!";
+    assert_eq!(english.polarity_scores(text), analyzer.polarity_scores(text));
+
+    let mut custom = analyzer.clone();
+    assert!(custom.contrasts_mut().insert("however"));
+    assert_eq!(custom.polarity_scores("good however slow"), analyzer.polarity_scores("good but slow"));
+    assert!(analyzer.polarity_scores("good however slow") != analyzer.polarity_scores("good but slow"));
+    let contrasts = ::ContrastTable::from_reader("but\nyet\n".as_bytes()).unwrap();
+    assert!(contrasts.contains("YET") && contrasts.len() == 2);
+}
+
+#[cfg(all(feature = "spanish", feature = "portuguese", feature = "german", feature = "french"))]
+#[test]
+fn language_packs_test() {
+    let texts = [
+        (::Language::Spanish, "la película es buena", "la película es muy buena", "la película no es buena",
+         "es buena pero aburrida"),
+        (::Language::Portuguese, "esse filme ficou bom", "esse filme ficou muito bom", "esse filme não ficou bom",
+         "ficou bom mas chato"),
+        (::Language::German, "der Film ist gut", "der Film ist sehr gut", "der Film ist nicht gut",
+         "gut aber langweilig"),
+        (::Language::French, "le film est bon", "le film est très bon", "le film est pas bon",
+         "bon mais ennuyeux"),
+    ];
+    let english = ::SentimentIntensityAnalyzer::new();
+    for &(language, plain, boosted, negated, contrast) in &texts {
+        let analyzer = ::SentimentIntensityAnalyzer::for_language(language).unwrap();
+        assert_eq!(analyzer.language(), language);
+        assert_eq!(analyzer.polarity_scores(plain), english.polarity_scores("the film is good"));
+        assert_eq!(analyzer.polarity_scores(boosted), english.polarity_scores("the film is very good"));
+        assert_eq!(analyzer.polarity_scores(negated), english.polarity_scores("the film is not good"));
+        assert!(analyzer.polarity_scores(contrast).compound < 0.0);
+        assert!(english.polarity_scores(plain).compound == 0.0);
+    }
+
+    // Packs keep the scoring constants and tokenizer set on the builder
+    let pack = ::Language::Spanish.pack().unwrap();
+    let analyzer = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { normalization_alpha: 5.0, ..::VaderConfig::default() })
+        .language_pack(pack)
+        .build();
+    assert!(analyzer.config().normalization_alpha == 5.0);
+    assert!(analyzer.negations().contains("nunca") && analyzer.rules().position("no").is_none());
+}
+
+#[test]
+fn language_detection_test() {
+    let texts = [
+        ("en", "The service was slow but the food was lovely"),
+        ("es", "El servicio fue lento pero la comida estaba buenísima"),
+        ("pt", "O atendimento foi lento mas a comida estava ótima"),
+        ("de", "Der Service war langsam, aber das Essen war sehr lecker"),
+        ("fr", "Le service était lent mais la nourriture était délicieuse"),
+        ("it", "Il servizio era lento ma il cibo era buonissimo"),
+        ("nl", "De bediening was traag maar het eten was heerlijk"),
+        ("ru", "Обслуживание было медленным, но еда отличная"),
+        ("ja", "サービスは遅かったけど料理は美味しかった"),
+        ("es", "@amigo muy bueno!! https://t.co/xyz"),
+    ];
+    for &(code, text) in &texts {
+        let guess = ::detect_language(text).unwrap();
+        assert_eq!(guess.code, code, "{}", text);
+        assert!(guess.confidence > 0.0 && guess.confidence <= 1.0);
+    }
+    assert_eq!(::detect_language("Das Wetter").unwrap().language(), Some(::Language::German));
+    assert_eq!(::detect_language("Il tempo").unwrap().language(), None);
+    assert_eq!(::detect_language("lol :) https://example.com/a-long-english-path"), None);
+    assert_eq!(::detect_language("12345 !!!"), None);
+
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let english = analyzer.polarity_scores_auto(texts[0].1).unwrap();
+    assert_eq!((english.language, english.scores), (::Language::English, analyzer.polarity_scores(texts[0].1)));
+    let short = analyzer.polarity_scores_auto("lol").unwrap();
+    assert_eq!((short.language, short.guess), (::Language::English, None));
+    let err = analyzer.polarity_scores_auto(texts[5].1).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported language \"it\"");
+    assert_eq!(analyzer.polarity_scores_auto(texts[1].1).is_ok(), cfg!(feature = "spanish"));
+
+    // Short English texts share too many trigrams with other languages to be routed anywhere else
+    for text in &["perfect", "cool", "super", "omg no", "nice", "terrible", "horrible", "fantastic", "great movie"] {
+        let auto = analyzer.polarity_scores_auto(text).unwrap();
+        assert_eq!((auto.language, auto.scores), (::Language::English, analyzer.polarity_scores(text)), "{}", text);
+    }
+    // but script is enough to tell, however short the text
+    assert!(analyzer.polarity_scores_auto("отлично").is_err());
+}
+
+#[cfg(all(feature = "spanish", feature = "portuguese", feature = "german", feature = "french"))]
+#[test]
+fn polarity_scores_auto_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { normalization_alpha: 5.0, ..::VaderConfig::default() })
+        .build();
+    let texts = [
+        (::Language::Spanish, "La comida es muy buena, pero el servicio es horrible"),
+        (::Language::Portuguese, "A comida é muito boa, mas o atendimento é péssimo"),
+        (::Language::German, "Das Essen ist sehr gut, aber der Service ist schrecklich"),
+        (::Language::French, "La nourriture est très bonne, mais le service est horrible"),
+    ];
+    for &(language, text) in &texts {
+        let auto = analyzer.polarity_scores_auto(text).unwrap();
+        assert_eq!(auto.language, language);
+        let expected = ::SentimentIntensityAnalyzer::builder()
+            .config(analyzer.config().clone())
+            .language_pack(language.pack().unwrap())
+            .build()
+            .polarity_scores(text);
+        assert_eq!(auto.scores, expected);
+        assert!(auto.scores.compound < 0.0);
+    }
+
+    // An analyzer for one language still routes English text to the English tables
+    let spanish = ::SentimentIntensityAnalyzer::for_language(::Language::Spanish).unwrap();
+    let text = "The food was great but the service was terrible";
+    let auto = spanish.polarity_scores_auto(text).unwrap();
+    assert_eq!(auto.language, ::Language::English);
+    assert_eq!(auto.scores, ::SentimentIntensityAnalyzer::new().polarity_scores(text));
+}
+
+fn python_analyzer() -> ::OwnedSentimentIntensityAnalyzer {
+    let config = ::VaderConfig { compat: ::Compat::Python, ..::VaderConfig::default() };
+    ::SentimentIntensityAnalyzer::builder().config(config).build()
+}
+
+fn corrected_analyzer() -> ::OwnedSentimentIntensityAnalyzer {
+    let config = ::VaderConfig { compat: ::Compat::Corrected, ..::VaderConfig::default() };
+    ::SentimentIntensityAnalyzer::builder().config(config).build()
+}
+
+// The valence of the last token of a text
+fn last_valence(analyzer: &::OwnedSentimentIntensityAnalyzer, text: &str) -> f64 {
+    analyzer.polarity_scores_explained(text).tokens.last().unwrap().valence
+}
+
+#[test]
+fn compat_test() {
+    let python = python_analyzer();
+    let legacy = ::SentimentIntensityAnalyzer::new();
+    let python_config = python.config().clone();
+    let valence = last_valence;
+
+    // "so" right before the lexicon word counts as "never so" in both, but only legacy scoring
+    // also reads a "this" two words before it that way
+    assert_eq!(valence(&python, "it is so good"), (1.9 + 0.293) * 1.25);
+    // Legacy scoring's special idiom window also counts the "so" as a booster a second time
+    assert!(valence(&legacy, "it is so good") > valence(&python, "it is so good"));
+    for analyzer in &[&python, &legacy] {
+        assert_eq!(valence(analyzer, "it was never this good"), 1.9 * 1.25 * 1.25);
+        assert!(valence(analyzer, "it is not doubt good") != valence(analyzer, "it is without doubt good"));
+        assert_eq!(valence(analyzer, "the least good"), 1.9 * -0.74);
+    }
+    assert_eq!(valence(&python, "it this is good"), 1.9);
+    assert_eq!(valence(&legacy, "it this is good"), 1.9 * 1.25);
+
+    // Only the original skips "at least" and "very least", and reads "no" as a negation
+    assert_eq!(valence(&python, "at least good"), 1.9);
+    assert_eq!(valence(&python, "the very least good"), 1.9 + 0.293 * 0.95);
+    assert!(python.polarity_scores("no problem").compound > 0.0);
+    assert!(python.polarity_scores("no good").compound < 0.0);
+
+    // A valence after "but" that equals a halved one before it scales that one again
+    let tokens: Vec<UniCase<&str>> = ["great", "but", "fine"].iter().map(|r| UniCase::new(*r)).collect();
+    let mut sentiments = vec![2.0, 0.0, 1.0];
+    ::but_check(&tokens, &mut sentiments, &::ContrastTable::default(), &python_config);
+    assert_eq!(sentiments, vec![0.5, 0.0, 1.0]);
+    let mut sentiments = vec![2.0, 0.0, 1.0];
+    ::but_check(&tokens, &mut sentiments, &::ContrastTable::default(), &::VaderConfig::default());
+    assert_eq!(sentiments, vec![1.0, 0.0, 1.5]);
+
+    // The original keeps one letter words, counts "I" as shouting and describes emoji by code point
+    let parsed = ::ParsedText::from_text("ok! I am a GOOD,cat", &python_config);
+    let tokens: Vec<&str> = parsed.tokens.iter().map(|t| t.as_ref()).collect();
+    assert_eq!(tokens, vec!["ok!", "I", "am", "a", "GOOD,cat"]);
+    assert!(!::ParsedText::from_text("I LOVE IT", &python_config).has_mixed_caps);
+    assert!(::ParsedText::from_text("I love it", &python_config).has_mixed_caps);
+    assert_eq!(python.append_emoji_descriptions("👨‍👩‍👧"), "man\u{200D} woman\u{200D} girl");
+    assert_eq!(python.append_emoji_descriptions("a👍🏽"), "a thumbs up medium skin tone");
+
+    // Laden idioms are this crate's addition
+    assert_eq!(python.polarity_scores("The company is in the black").compound, 0.0);
+
+    // The original doesn't count a lone '?' as emphasis and keeps neutral text neutral
+    assert_eq!(python.polarity_scores("Is it good?"), python.polarity_scores("Is it good"));
+    assert_eq!(python.polarity_scores("Hello there!!!").compound, 0.0);
+    // and has a few more special case idioms
+    assert_eq!(python.polarity_scores("at the bus stop").compound, 0.0);
+    // and only ever looks up the lowercase spelling of ":p"
+    assert_eq!(python.polarity_scores(":P").compound, 0.25);
+}
+
+// Compat::Legacy, the default, keeps the scores this crate always returned, mistakes included
+#[test]
+fn legacy_compat_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(analyzer.config().compat, ::Compat::Legacy);
+    let valence = |text| last_valence(&analyzer, text);
+    assert_eq!(valence("at least good"), 1.9 * -0.74);
+    assert!(valence("the very least good") < 0.0);
+    assert_eq!(valence("no good"), 1.9);
+    assert!(analyzer.polarity_scores("no problem").compound < 0.0);
+    assert!(analyzer.polarity_scores("!!!").compound < 0.0);
+    assert!(analyzer.polarity_scores("Hello there!!!").compound < 0.0);
+    assert!(analyzer.polarity_scores("Is it good?").compound > analyzer.polarity_scores("Is it good").compound);
+    assert!(analyzer.polarity_scores("at the bus stop").compound < 0.0);
+    let special = ::SpecialIdiomTable::default();
+    for phrase in &["bus stop", "beating heart", "broken heart"] {
+        assert_eq!(special.get(phrase), None);
+    }
+    // The later ":P" line replaces ":p"
+    assert_eq!(analyzer.polarity_scores(":P").compound, 0.33995005182504245);
+    assert_eq!(analyzer.polarity_scores("ok :P").compound, 0.6522905964954855);
+}
+
+#[test]
+fn corrected_never_test() {
+    let corrected = corrected_analyzer();
+    let legacy = ::SentimentIntensityAnalyzer::new();
+    // "so" or "this" only intensifies after a "never"
+    assert_eq!(last_valence(&corrected, "it this is good"), 1.9);
+    assert_eq!(last_valence(&corrected, "it was never this good"), 1.9 * 1.25);
+    assert_eq!(last_valence(&legacy, "it was never this good"), 1.9 * 1.25 * 1.25);
+    assert_eq!(last_valence(&corrected, "never so very good"), (1.9 + 0.293 * 0.95 + 0.293) * 1.25);
+    // and "doubt" only undoes a negation after a "without"
+    assert_eq!(last_valence(&corrected, "not any doubt good"), 1.9 * -0.74);
+    assert_eq!(last_valence(&legacy, "not any doubt good"), 1.9);
+    assert_eq!(last_valence(&corrected, "without any doubt good"), 1.9);
+}
+
+#[test]
+fn corrected_least_test() {
+    let corrected = corrected_analyzer();
+    assert_eq!(last_valence(&corrected, "at least good"), 1.9);
+    assert_eq!(last_valence(&corrected, "the very least good"), 1.9 + 0.293 * 0.95);
+    assert_eq!(last_valence(&corrected, "the least good"), 1.9 * -0.74);
+}
+
+#[test]
+fn corrected_punctuation_test() {
+    let corrected = corrected_analyzer();
+    let legacy = ::SentimentIntensityAnalyzer::new();
+    assert_eq!(corrected.polarity_scores("Hello there!!!").compound, 0.0);
+    assert_eq!(corrected.polarity_scores("!!!").compound, 0.0);
+    let social = ::SentimentIntensityAnalyzer::builder()
+        .config(::VaderConfig { social_media: true, compat: ::Compat::Corrected, ..::VaderConfig::default() })
+        .build();
+    assert_eq!(social.polarity_scores("@love!").compound, 0.0);
+    // Text with a sentiment is still emphasized
+    assert_eq!(corrected.polarity_scores("good!!!"), legacy.polarity_scores("good!!!"));
+    assert_eq!(corrected.polarity_scores("bad?!"), legacy.polarity_scores("bad?!"));
+}
+
+#[test]
+fn corrected_special_idioms_test() {
+    let corrected = corrected_analyzer();
+    // A booster before a word is only counted once
+    assert_eq!(last_valence(&corrected, "it is so good"), 1.9 + 0.293);
+    assert_eq!(last_valence(&corrected, "it is very very good"), 1.9 + 0.293 + 0.293 * 0.95);
+    let explanation = corrected.polarity_scores_explained("it is so good");
+    let rules: Vec<::Rule> = explanation.tokens[3].adjustments.iter().map(|a| a.rule).collect();
+    assert_eq!(rules, vec![::Rule::Booster]);
+    // Idioms are matched on whole tokens
+    assert_eq!(corrected.polarity_scores("at the bus stop").compound, 0.0);
+    assert_eq!(last_valence(&corrected, "it is the bomb"), 3.0);
+    assert_eq!(last_valence(&corrected, "the bombastic good"), 1.9);
+}
+
+#[test]
+fn python_parity_test() {
+    let analyzer = python_analyzer();
+    let corpus = include_str!("../tests/parity/vader_python.tsv");
+    // The original rounds its scores to 3 and 4 (for the compound score) decimals
+    let rounded = |neg: f64, neu: f64, pos: f64, compound: f64| {
+        format!("{:.3} {:.3} {:.3} {:.4}", neg, neu, pos, compound)
+    };
+    let mut count = 0;
+    let mut mismatches = Vec::new();
+    for line in corpus.lines().skip(1) {
+        let columns: Vec<&str> = line.split('\t').collect();
+        let expected: Vec<f64> = columns[1..].iter().map(|c| c.parse().unwrap()).collect();
+        let expected = rounded(expected[0], expected[1], expected[2], expected[3]);
+        let scores = analyzer.polarity_scores(columns[0]);
+        let actual = rounded(scores.neg, scores.neu, scores.pos, scores.compound);
+        if actual != expected {
+            mismatches.push(format!("{:?}: expected {}, got {}", columns[0], expected, actual));
+        }
+        count += 1;
+    }
+    assert!(count > 3000);
+    assert!(mismatches.is_empty(), "{} of {} sentences differ from vaderSentiment:\n{}",
+            mismatches.len(), count, mismatches.join("\n"));
+}
+
+fn batch_corpus() -> Vec<String> {
+    let sentences = ["VADER is smart, handsome, and funny!", "The book was only kind of good.",
+                     "Today only kinda sux! But I'll get by, lol", "Catch utf-8 emoji such as 💘 and 💋 and 😁",
+                     "", "Not bad at all", "I am feeling under the weather 👋🏽", "It was the bomb, yeah right",
+                     "NEVER so happy?!?!", "The plot was good, but the characters are uncompelling."];
+    (0..200).map(|i| sentences[i % sentences.len()].repeat(i % 3 + 1)).collect()
+}
+
+#[test]
+fn batch_test() {
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let texts = batch_corpus();
+    let sequential: Vec<::SentimentScores> = texts.iter().map(|t| analyzer.polarity_scores(t)).collect();
+    assert_eq!(analyzer.polarity_scores_batch(&texts), sequential);
+    let borrowed: Vec<&str> = texts.iter().map(|t| t.as_str()).collect();
+    assert_eq!(analyzer.polarity_scores_batch(&borrowed), sequential);
+    assert!(analyzer.polarity_scores_batch::<&str>(&[]).is_empty());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_batch_test() {
+    use rayon::prelude::*;
+
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let texts = batch_corpus();
+    let sequential = analyzer.polarity_scores_batch(&texts);
+    assert_eq!(analyzer.polarity_scores_par_batch(&texts), sequential);
+    let from_iter: Vec<::SentimentScores> = analyzer.polarity_scores_par_iter(texts.par_iter().map(|t| t.as_str())).collect();
+    assert_eq!(from_iter, sequential);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_test() {
+    use serde_json::{self, json, Value};
+
+    let analyzer = ::SentimentIntensityAnalyzer::new();
+    let scores = analyzer.polarity_scores("VADER is smart, handsome, and funny.");
+    let value = serde_json::to_value(scores).unwrap();
+    let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+    assert_eq!(keys, vec!["compound", "neg", "neu", "pos"]);
+    assert_eq!(serde_json::from_value::<::SentimentScores>(value).unwrap(), scores);
+    assert_eq!(serde_json::to_value(scores.label()).unwrap(), json!("positive"));
+
+    let explanation = analyzer.polarity_scores_explained("The plot was not very GOOD, but the acting is great!");
+    let value = serde_json::to_value(&explanation).unwrap();
+    assert_eq!(value["tokens"][5]["adjustments"][0]["rule"], json!("caps_emphasis"));
+    assert_eq!(value["tokens"][5]["span"], json!({"start": 22, "end": 26, "char_start": 22, "char_end": 26}));
+    assert_eq!(value["tokens"][0]["base_valence"], Value::Null);
+    assert_eq!(serde_json::from_value::<::Explanation>(value).unwrap(), explanation);
+    let document = analyzer.document_scores("The room was lovely. But the food was awful!");
+    let json = serde_json::to_string(&document).unwrap();
+    assert_eq!(serde_json::from_str::<::DocumentScores>(&json).unwrap(), document);
+
+    // Entries without ratings are plain numbers, like in the Python analyzer's lexicon dict
+    let lexicon: ::Lexicon = serde_json::from_str(r#"{"bullish": 2.5, "bearish": -2.5, "meh": {"mean": -0.3, "std_dev": 0.5, "ratings": [0, -1]}}"#).unwrap();
+    assert_eq!(lexicon.get("BULLISH"), Some(2.5));
+    assert_eq!(lexicon.entry("meh").unwrap().ratings, vec![0, -1]);
+    assert_eq!(serde_json::to_string(&lexicon).unwrap(),
+               r#"{"bearish":-2.5,"bullish":2.5,"meh":{"mean":-0.3,"std_dev":0.5,"ratings":[0,-1]}}"#);
+    let analyzer = ::SentimentIntensityAnalyzer::from_custom_lexicon(&lexicon);
+    let reloaded: ::Lexicon = serde_json::from_value(serde_json::to_value(analyzer.lexicon().unwrap()).unwrap()).unwrap();
+    assert_eq!(reloaded, lexicon);
+    let default = ::SentimentIntensityAnalyzer::new();
+    let json = serde_json::to_string(default.lexicon().unwrap()).unwrap();
+    assert_eq!(&serde_json::from_str::<::Lexicon>(&json).unwrap(), &*::DEFAULT_LEXICON);
+    assert!(serde_json::from_str::<::Lexicon>(r#"{"good": "very"}"#).is_err());
+}
+
+// Run with `wasm-pack test --node --features wasm`
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm {
+    use js_sys::{Object, Reflect};
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use wasm::WasmSentimentIntensityAnalyzer;
+
+    fn get(object: &Object, key: &str) -> f64 {
+        Reflect::get(object, &JsValue::from_str(key)).unwrap().as_f64().unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn polarity_scores_test() {
+        let analyzer = WasmSentimentIntensityAnalyzer::new(None).unwrap();
+        let text = "VADER is smart, handsome, and funny.";
+        let scores = analyzer.polarity_scores(text);
+        let expected = ::SentimentIntensityAnalyzer::new().polarity_scores(text);
+        assert_eq!(get(&scores, "compound"), expected.compound);
+        assert_eq!(get(&scores, "neg") + get(&scores, "neu") + get(&scores, "pos"),
+                   expected.neg + expected.neu + expected.pos);
+    }
+
+    #[wasm_bindgen_test]
+    fn custom_lexicon_test() {
+        let analyzer = WasmSentimentIntensityAnalyzer::new(Some("bullish\t2.5\n".to_string())).unwrap();
+        assert!(get(&analyzer.polarity_scores("very bullish"), "compound") > 0.0);
+        assert_eq!(get(&analyzer.polarity_scores("good"), "compound"), 0.0);
+        assert!(WasmSentimentIntensityAnalyzer::new(Some("bullish".to_string())).is_err());
+    }
+}
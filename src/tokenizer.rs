@@ -0,0 +1,95 @@
+use std::ops::Range;
+
+use {is_punctuation, is_python_whitespace};
+
+/**
+ * A token found by a `Tokenizer`, with where it is in the text that was tokenized
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'t> {
+    // The token as it is looked up in the lexicon and word tables
+    pub text: &'t str,
+    // Byte range of the token in the tokenized text
+    pub span: Range<usize>,
+    // Emoticons like ":)" or "<3" are kept whole rather than stripped of punctuation
+    pub is_emoticon: bool,
+}
+
+impl<'t> Token<'t> {
+    pub fn new(text: &'t str, span: Range<usize>) -> Token<'t> {
+        Token { text, span, is_emoticon: false }
+    }
+
+    /**
+     * A token that is a slice of the tokenized text, with its span worked out from where the
+     * slice is. Panics if `slice` isn't part of `text`.
+     **/
+    pub fn from_slice(text: &'t str, slice: &'t str) -> Token<'t> {
+        let start = (slice.as_ptr() as usize).checked_sub(text.as_ptr() as usize)
+            .filter(|&start| start + slice.len() <= text.len())
+            .expect("token is not a slice of the tokenized text");
+        Token::new(slice, start..start + slice.len())
+    }
+
+    pub fn emoticon(mut self, is_emoticon: bool) -> Token<'t> {
+        self.is_emoticon = is_emoticon;
+        self
+    }
+}
+
+/**
+ * Splits text into the tokens that are scored. The text has already had its emoji replaced by
+ * their descriptions, and elongated words normalized if that is enabled. Token text doesn't
+ * have to be a slice of the tokenized text, but the span should cover what the token was made
+ * from, since it is used to report where the token is in the original text.
+ **/
+pub trait Tokenizer: Send + Sync {
+    fn tokenize<'t>(&self, text: &'t str, tokens: &mut Vec<Token<'t>>);
+}
+
+/**
+ * The tokenizer used unless another is set: splits on whitespace, drops one char tokens and
+ * strips punctuation around words, keeping emoticons whole.
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultTokenizer;
+
+impl Tokenizer for DefaultTokenizer {
+    fn tokenize<'t>(&self, text: &'t str, tokens: &mut Vec<Token<'t>>) {
+        for word in text.split_whitespace().filter(|s| s.chars().count() > 1) {
+            tokens.push(word_token(text, word));
+        }
+    }
+}
+
+// Removes punctuation from words, ie "hello!!!" -> "hello", ",don't??" -> "don't" and "¡sí!" -> "sí"
+// Keeps most emoticons, ie ":^)" -> ":^)"
+pub(crate) fn word_token<'t>(text: &'t str, word: &'t str) -> Token<'t> {
+    let stripped = word.trim_matches(is_punctuation);
+    if stripped.chars().count() <= 1 {
+        return Token::from_slice(text, word).emoticon(true);
+    }
+    Token::from_slice(text, stripped)
+}
+
+/**
+ * Tokenizes like the Python implementation, which keeps one letter words and only strips
+ * punctuation from tokens that are longer than two chars without it, ie "ok!" stays "ok!"
+ **/
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PythonTokenizer;
+
+impl Tokenizer for PythonTokenizer {
+    fn tokenize<'t>(&self, text: &'t str, tokens: &mut Vec<Token<'t>>) {
+        for word in text.split(is_python_whitespace).filter(|s| !s.is_empty()) {
+            let stripped = word.trim_matches(|c: char| c.is_ascii_punctuation());
+            let count = stripped.chars().count();
+            if count <= 2 {
+                let is_emoticon = count <= 1 && stripped.len() < word.len();
+                tokens.push(Token::from_slice(text, word).emoticon(is_emoticon));
+            } else {
+                tokens.push(Token::from_slice(text, stripped));
+            }
+        }
+    }
+}
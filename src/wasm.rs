@@ -0,0 +1,57 @@
+/*!
+ * JavaScript bindings, built with `wasm-pack build --features wasm`:
+ *
+ * ```js
+ * import { SentimentIntensityAnalyzer } from "vader_sentiment";
+ *
+ * const analyzer = new SentimentIntensityAnalyzer();
+ * analyzer.polarityScores("VADER is smart, handsome, and funny.");
+ * // { neg: 0, neu: 0.254..., pos: 0.745..., compound: 0.831... }
+ * ```
+ **/
+
+use std::sync::Arc;
+
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use {Lexicon, OwnedSentimentIntensityAnalyzer, SentimentScores};
+
+#[wasm_bindgen(js_name = SentimentIntensityAnalyzer)]
+pub struct WasmSentimentIntensityAnalyzer {
+    analyzer: OwnedSentimentIntensityAnalyzer,
+}
+
+#[wasm_bindgen(js_class = SentimentIntensityAnalyzer)]
+impl WasmSentimentIntensityAnalyzer {
+    /**
+     * Uses the built-in lexicon, or parses `lexicon` if given. It has the format of
+     * vader_lexicon.txt, one `word<TAB>valence` line per word.
+     **/
+    #[wasm_bindgen(constructor)]
+    pub fn new(lexicon: Option<String>) -> Result<WasmSentimentIntensityAnalyzer, JsError> {
+        let analyzer = match lexicon {
+            Some(lexicon) => {
+                let lexicon: Lexicon = lexicon.parse().map_err(|e| JsError::new(&format!("invalid lexicon: {}", e)))?;
+                OwnedSentimentIntensityAnalyzer::from_shared_lexicon(Arc::new(lexicon))
+            },
+            None => OwnedSentimentIntensityAnalyzer::new(),
+        };
+        Ok(WasmSentimentIntensityAnalyzer { analyzer })
+    }
+
+    //Returns a plain `{ neg, neu, pos, compound }` object
+    #[wasm_bindgen(js_name = polarityScores)]
+    pub fn polarity_scores(&self, text: &str) -> Object {
+        scores_object(&self.analyzer.polarity_scores(text))
+    }
+}
+
+fn scores_object(scores: &SentimentScores) -> Object {
+    let object = Object::new();
+    for &(key, value) in &[("neg", scores.neg), ("neu", scores.neu), ("pos", scores.pos), ("compound", scores.compound)] {
+        // Setting a property on a fresh plain object can't fail
+        let _ = Reflect::set(&object, &JsValue::from_str(key), &JsValue::from_f64(value));
+    }
+    object
+}
@@ -0,0 +1,54 @@
+use std::borrow::{Borrow, Cow};
+use std::hash::{Hash, Hasher};
+use unicase::UniCase;
+
+/**
+ * Case-insensitive key for the lexicon and word tables. Lookups go through `WordRef` so that
+ * querying them with a token doesn't have to allocate.
+ **/
+#[derive(Debug, Clone)]
+pub(crate) struct Word(pub Cow<'static, str>);
+
+#[repr(transparent)]
+pub(crate) struct WordRef(str);
+
+impl WordRef {
+    pub fn new(word: &str) -> &WordRef {
+        // Safe because WordRef is a transparent wrapper around str
+        unsafe { &*(word as *const str as *const WordRef) }
+    }
+}
+
+impl Hash for WordRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        UniCase::new(&self.0).hash(state)
+    }
+}
+
+impl PartialEq for WordRef {
+    fn eq(&self, other: &WordRef) -> bool {
+        UniCase::new(&self.0) == UniCase::new(&other.0)
+    }
+}
+
+impl Eq for WordRef {}
+
+impl Borrow<WordRef> for Word {
+    fn borrow(&self) -> &WordRef {
+        WordRef::new(&self.0)
+    }
+}
+
+impl Hash for Word {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        WordRef::new(&self.0).hash(state)
+    }
+}
+
+impl PartialEq for Word {
+    fn eq(&self, other: &Word) -> bool {
+        WordRef::new(&self.0) == WordRef::new(&other.0)
+    }
+}
+
+impl Eq for Word {}
@@ -0,0 +1,60 @@
+"""Regenerates vader_python.tsv, the corpus that `python_parity_test` in src/tests.rs checks
+`Compat::Python` against, from the scores of the Python vaderSentiment package.
+
+    pip install vaderSentiment==3.3.2
+    python3 tests/parity/generate.py                  # rescore the sentences already in the corpus
+    python3 tests/parity/generate.py sentences.txt    # or score new ones, one per line
+
+The package is pointed at the lexicons in src/resources, so a lexicon change shows up as a
+change in the expected scores rather than as a parity failure.
+
+Where the sentences came from: the first 16 rows are the examples from vaderSentiment's README
+and demo. They are followed by sentences built by randomly combining templates: an optional
+opener ("Honestly,", "Ugh...", "Not gonna lie,"), a subject, a verb, optional negations,
+boosters and "least"/"kind of" modifiers, a sentiment word or special case idiom, then optional
+'!'/'?' runs and an emoticon or emoji, with words put in caps at random. First come one clause
+sentences, then two clauses joined by a conjunction ("but", "and", "although", "yet", "nor",
+...), then comma separated lists of idioms and negated phrases. The last rows fill edge case
+patterns for the tokenizer, caps and punctuation ("X!!! a b c", "((X))", "I X,it") with
+sentiment words. The script that built the
+templated rows wasn't kept, so this file is the source of truth for the sentences: rescore it
+in place, and add new sentences from a file instead of rebuilding it.
+"""
+
+import csv
+import os
+import sys
+
+from vaderSentiment.vaderSentiment import SentimentIntensityAnalyzer
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+RESOURCES = os.path.join(HERE, "..", "..", "src", "resources")
+CORPUS = os.path.join(HERE, "vader_python.tsv")
+
+
+def read_sentences(path):
+    with open(path, encoding="utf-8") as f:
+        if path == CORPUS:
+            rows = csv.reader(f, delimiter="\t", quoting=csv.QUOTE_NONE)
+            next(rows)
+            return [row[0] for row in rows]
+        return [line.rstrip("\n") for line in f if line.strip()]
+
+
+def main():
+    sentences = read_sentences(sys.argv[1] if len(sys.argv) > 1 else CORPUS)
+    analyzer = SentimentIntensityAnalyzer(os.path.join(RESOURCES, "vader_lexicon.txt"),
+                                          os.path.join(RESOURCES, "emoji_utf8_lexicon.txt"))
+    with open(CORPUS, "w", encoding="utf-8", newline="") as f:
+        f.write("text\tneg\tneu\tpos\tcompound\n")
+        for sentence in sentences:
+            if "\t" in sentence or "\n" in sentence:
+                sys.exit("sentences can't contain tabs or newlines: %r" % sentence)
+            scores = analyzer.polarity_scores(sentence)
+            f.write("%s\t%s\t%s\t%s\t%s\n" % (sentence, scores["neg"], scores["neu"], scores["pos"],
+                                              scores["compound"]))
+    print("wrote %d sentences to %s" % (len(sentences), CORPUS))
+
+
+if __name__ == "__main__":
+    main()